@@ -1,9 +1,9 @@
 use std::path::PathBuf;
 
 use anyhow::{anyhow, Result};
-use rust_apns::{Endpoint, InterruptionLevel, Priority, PushType};
 use clap::{ArgGroup, Parser};
 use humantime::parse_duration;
+use rust_apns::{DeviceToken, Endpoint, InterruptionLevel, LiveActivityEvent, Priority, PushType};
 use time::{format_description::well_known::Iso8601, OffsetDateTime};
 use uuid::Uuid;
 
@@ -12,13 +12,12 @@ use uuid::Uuid;
 #[command(author, version, about)]
 #[command(group(
     ArgGroup::new("authentication")
-        .args(["client_pem_file", "key_pem_file"])
+        .args(["client_pem_file", "pkcs12_file", "key_pem_file"])
         .required(true)
 ), group(
     ArgGroup::new("certificate")
         .conflicts_with("token")
-        .arg("client_pem_file")
-        .requires("client_pem_file")
+        .args(["client_pem_file", "pkcs12_file"])
 ), group(
     ArgGroup::new("token")
         .conflicts_with("certificate")
@@ -33,6 +32,22 @@ pub struct Cli {
     #[arg(long, env)]
     pub client_pem_file: Option<PathBuf>,
 
+    /// The private key of the client certificate, if it isn't in the
+    /// client PEM file.
+    #[arg(long, env, requires = "client_pem_file")]
+    pub client_key_file: Option<PathBuf>,
+
+    /// The password of an encrypted client private key.
+    #[arg(long, env, requires = "client_key_file")]
+    pub client_key_password: Option<String>,
+
+    /// The client certificate and its private key as a PKCS12 database.
+    #[arg(long, env, requires = "pkcs12_password")]
+    pub pkcs12_file: Option<PathBuf>,
+
+    #[arg(long, env, requires = "pkcs12_file")]
+    pub pkcs12_password: Option<String>,
+
     #[arg(long, env)]
     pub key_id: Option<String>,
 
@@ -50,7 +65,7 @@ pub struct Cli {
 
     /// The hex-encoded device token.
     #[arg(long, env)]
-    pub device_token: String,
+    pub device_token: DeviceToken,
 
     /// The push type of the notification to send.
     #[arg(long, env, default_value_t = PushType::Alert)]
@@ -137,6 +152,43 @@ pub struct Cli {
     #[arg(long, env)]
     pub relevance_score: Option<f64>,
 
+    /// The flag to reload the widgets or controls of the app.
+    #[arg(long, env, default_value_t = false)]
+    pub content_changed: bool,
+
+    /// The values filled into the URL format string of a website push
+    /// package, separated by commas.
+    #[arg(long, env, value_delimiter = ',')]
+    pub url_args: Option<Vec<String>>,
+
+    /// The time of a Live Activity update.
+    #[arg(long, env, value_parser = parse_timestamp)]
+    pub timestamp: Option<OffsetDateTime>,
+
+    /// Whether to start, update or end a Live Activity.
+    #[arg(long, env)]
+    pub event: Option<LiveActivityEvent>,
+
+    /// The dynamic content of a Live Activity.
+    #[arg(long, env)]
+    pub content_state: Option<serde_json::Value>,
+
+    /// The time at which a Live Activity becomes outdated.
+    #[arg(long, env, value_parser = parse_timestamp)]
+    pub stale_date: Option<OffsetDateTime>,
+
+    /// The time at which the system removes an ended Live Activity.
+    #[arg(long, env, value_parser = parse_timestamp)]
+    pub dismissal_date: Option<OffsetDateTime>,
+
+    /// The name of the `ActivityAttributes` type of a Live Activity.
+    #[arg(long, env)]
+    pub attributes_type: Option<String>,
+
+    /// The static attributes of a Live Activity.
+    #[arg(long, env)]
+    pub attributes: Option<serde_json::Value>,
+
     /// Additional data to send.
     #[arg(long, env)]
     pub user_info: Option<serde_json::Value>,
@@ -147,7 +199,9 @@ fn parse_timestamp(arg: &str) -> Result<OffsetDateTime> {
         Ok(timestamp) => Ok(timestamp),
         Err(duration_err) => match parse_duration(arg) {
             Ok(duration) => Ok(OffsetDateTime::now_utc() + duration),
-            Err(timestamp_err) => Err(anyhow!("Invalid expiration; invalid timestamp: {timestamp_err}; invalid duration: {duration_err}")),
+            Err(timestamp_err) => Err(anyhow!(
+                "Invalid expiration; invalid timestamp: {timestamp_err}; invalid duration: {duration_err}"
+            )),
         },
     }
 }
@@ -1,10 +1,8 @@
 use std::fs;
 
 use anyhow::Result;
-use rust_apns::{
-    Alert, Authentication, CertificateAuthority, ClientBuilder, InterruptionLevel, Request, Sound,
-};
 use clap::Parser;
+use rust_apns::{Alert, Authentication, CertificateAuthority, ClientBuilder, InterruptionLevel, Request, Sound};
 
 mod cli;
 
@@ -33,15 +31,29 @@ pub async fn main() -> Result<()> {
     }
 
     let mut client_pem = None;
+    let mut client_key = None;
+    let mut pkcs12 = None;
     let mut key_pem = None;
-    if let Some(client_pem_file) = &cli.client_pem_file {
+    if let (Some(client_pem_file), Some(client_key_file)) = (&cli.client_pem_file, &cli.client_key_file) {
+        client_pem = Some(fs::read(client_pem_file)?);
+        client_key = Some(fs::read(client_key_file)?);
+        builder.authentication = Some(Authentication::CertificateAndKey {
+            cert_pem: client_pem.as_ref().unwrap(),
+            key_pem: client_key.as_ref().unwrap(),
+            key_password: cli.client_key_password.as_deref(),
+        })
+    } else if let Some(client_pem_file) = &cli.client_pem_file {
         client_pem = Some(fs::read(client_pem_file)?);
         builder.authentication = Some(Authentication::Certificate {
             client_pem: client_pem.as_ref().unwrap(),
         })
-    } else if let (Some(key_id), Some(key_pem_file), Some(team_id)) =
-        (&cli.key_id, &cli.key_pem_file, &cli.team_id)
-    {
+    } else if let (Some(pkcs12_file), Some(password)) = (&cli.pkcs12_file, &cli.pkcs12_password) {
+        pkcs12 = Some(fs::read(pkcs12_file)?);
+        builder.authentication = Some(Authentication::Pkcs12 {
+            pkcs12: pkcs12.as_ref().unwrap(),
+            password,
+        })
+    } else if let (Some(key_id), Some(key_pem_file), Some(team_id)) = (&cli.key_id, &cli.key_pem_file, &cli.team_id) {
         key_pem = Some(fs::read(key_pem_file)?);
         builder.authentication = Some(Authentication::Token {
             key_id,
@@ -89,6 +101,15 @@ pub async fn main() -> Result<()> {
         target_content_id: cli.target_content_id,
         interruption_level: cli.interruption_level,
         relevance_score: cli.relevance_score,
+        content_changed: cli.content_changed,
+        url_args: cli.url_args,
+        timestamp: cli.timestamp,
+        event: cli.event,
+        content_state: cli.content_state,
+        stale_date: cli.stale_date,
+        dismissal_date: cli.dismissal_date,
+        attributes_type: cli.attributes_type,
+        attributes: cli.attributes,
         user_info: cli.user_info,
     };
 
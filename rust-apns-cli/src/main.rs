@@ -1,4 +1,4 @@
 #[tokio::main]
 async fn main() {
-    apple_apns_cli::main().await.unwrap()
+    rust_apns_cli::main().await.unwrap()
 }
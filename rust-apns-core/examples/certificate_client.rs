@@ -40,22 +40,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
 
     // Connecting to APNs using a client certificate
     let new_client = || -> Result<Client, Box<dyn std::error::Error + Sync + Send>> {
-        #[cfg(feature = "openssl")]
-        {
-            // Which service to call, test or production?
-            let endpoint = if sandbox {
-                Endpoint::Development
-            } else {
-                Endpoint::Production
-            };
+        // Which service to call, test or production?
+        let endpoint = if sandbox {
+            Endpoint::Development
+        } else {
+            Endpoint::Production
+        };
 
-            let mut certificate = std::fs::File::open(certificate_file)?;
-            Ok(Client::certificate(&mut certificate, &password, endpoint)?)
-        }
-        #[cfg(all(not(feature = "openssl"), feature = "ring"))]
-        {
-            Err("ring does not support loading of certificates".into())
-        }
+        let mut certificate = std::fs::File::open(certificate_file)?;
+        Ok(Client::certificate(&mut certificate, &password, endpoint)?)
     };
     let client = new_client()?;
 
@@ -0,0 +1,374 @@
+//! Inspecting provider client certificates
+
+use std::time::Duration;
+use time::OffsetDateTime;
+use x509_parser::{
+    certificate::X509Certificate,
+    der_parser::{ber::BerObject, parse_der},
+    pem::Pem,
+};
+
+use crate::error::Error;
+
+use super::{builder::parse_pkcs12, endpoint::Endpoint, header::PushType};
+
+/// Marks a certificate for the development environment.
+const DEVELOPMENT_OID: &str = "1.2.840.113635.100.6.3.1";
+
+/// Marks a certificate for the production environment.
+const PRODUCTION_OID: &str = "1.2.840.113635.100.6.3.2";
+
+/// Lists the topics of the certificate and the kind of each topic.
+const TOPICS_OID: &str = "1.2.840.113635.100.6.3.6";
+
+/// The common name attribute of the subject.
+const COMMON_NAME_OID: &str = "2.5.4.3";
+
+/// The UID attribute of the subject, holding the bundle ID.
+const UID_OID: &str = "0.9.2342.19200300.100.1.1";
+
+/// The organizational unit attribute of the subject, holding the team ID.
+const ORGANIZATIONAL_UNIT_OID: &str = "2.5.4.11";
+
+/// How long before the expiry of a client certificate building a client
+/// logs a warning.
+#[cfg(feature = "tracing")]
+const EXPIRY_WARNING: Duration = Duration::from_secs(60 * 60 * 24 * 30);
+
+/// What an APNs provider certificate allows to send, and until when.
+///
+/// Inspecting the certificate before connecting lets the topic be picked
+/// from the certificate, an upcoming expiry be noticed, and a certificate for
+/// the wrong environment be rejected before APNs answers with
+/// `BadCertificateEnvironment`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CertificateInfo {
+    /// The common name of the subject, e.g. `Apple Push Services:
+    /// com.example.myapp`.
+    pub common_name: Option<String>,
+
+    /// The bundle ID of the app, from the UID attribute of the subject.
+    pub bundle_id: Option<String>,
+
+    /// The team ID, from the organizational unit of the subject.
+    pub team_id: Option<String>,
+
+    /// The topics the certificate can send to, from the
+    /// 1.2.840.113635.100.6.3.6 extension.
+    pub topics: Vec<CertificateTopic>,
+
+    /// Whether the certificate is valid for the development environment.
+    pub development: bool,
+
+    /// Whether the certificate is valid for the production environment.
+    pub production: bool,
+
+    /// The start of the validity period.
+    pub not_before: OffsetDateTime,
+
+    /// The end of the validity period, after which APNs refuses the
+    /// certificate.
+    pub not_after: OffsetDateTime,
+}
+
+/// A topic listed in a provider certificate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CertificateTopic {
+    /// The topic, e.g. `com.example.myapp.voip`.
+    pub topic: String,
+
+    /// The kinds of service the topic is registered for, e.g. `app`, `voip`
+    /// or `complication`.
+    pub kinds: Vec<String>,
+}
+
+impl CertificateInfo {
+    /// Inspect the certificate of a PKCS12 database, the material passed to
+    /// [Client::certificate](../client/struct.Client.html#method.certificate).
+    pub fn from_pkcs12(pkcs12: &[u8], password: &str) -> Result<CertificateInfo, Error> {
+        let (cert_pem, _) = parse_pkcs12(pkcs12, password)?;
+
+        CertificateInfo::from_pem(&cert_pem)
+    }
+
+    /// Inspect the first certificate of a PEM file, which may also hold the
+    /// private key and intermediate certificates.
+    pub fn from_pem(cert_pem: &[u8]) -> Result<CertificateInfo, Error> {
+        for pem in Pem::iter_from_buffer(cert_pem) {
+            let pem = pem.map_err(invalid)?;
+
+            if pem.label == "CERTIFICATE" {
+                return CertificateInfo::from_der(&pem.contents);
+            }
+        }
+
+        Err(Error::ClientConfigError(
+            "Invalid client certificate: no certificate found".into(),
+        ))
+    }
+
+    /// Inspect a DER encoded certificate.
+    pub fn from_der(cert_der: &[u8]) -> Result<CertificateInfo, Error> {
+        let (_, certificate) = x509_parser::parse_x509_certificate(cert_der).map_err(invalid)?;
+
+        let common_name = subject_attribute(&certificate, COMMON_NAME_OID);
+        let mut topics = Vec::new();
+        let mut development = false;
+        let mut production = false;
+
+        for extension in certificate.extensions() {
+            match extension.oid.to_id_string().as_str() {
+                DEVELOPMENT_OID => development = true,
+                PRODUCTION_OID => production = true,
+                TOPICS_OID => topics = parse_topics(extension.value)?,
+                _ => (),
+            }
+        }
+
+        // Older certificates only name their environment in the subject.
+        if !development && !production {
+            match common_name.as_deref() {
+                Some(name) if name.starts_with("Apple Development IOS Push Services") => development = true,
+                Some(name) if name.starts_with("Apple Production IOS Push Services") => production = true,
+                Some(name) if name.starts_with("Apple Push Services") => {
+                    development = true;
+                    production = true;
+                }
+                _ => (),
+            }
+        }
+
+        Ok(CertificateInfo {
+            bundle_id: subject_attribute(&certificate, UID_OID),
+            team_id: subject_attribute(&certificate, ORGANIZATIONAL_UNIT_OID),
+            common_name,
+            topics,
+            development,
+            production,
+            not_before: certificate.validity().not_before.to_datetime(),
+            not_after: certificate.validity().not_after.to_datetime(),
+        })
+    }
+
+    /// The topic to send a notification of the given push type to, e.g. the
+    /// bundle ID for alerts or the bundle ID followed by `.voip` for VoIP
+    /// notifications. `None` if the certificate doesn't cover the push type.
+    pub fn topic_for(&self, push_type: PushType) -> Option<&str> {
+        let bundle_id = self.bundle_id.as_deref()?;
+        let topic = push_type.topic(bundle_id);
+
+        if self.topics.is_empty() && push_type.topic_suffix().is_empty() {
+            return Some(bundle_id);
+        }
+
+        self.topics
+            .iter()
+            .find(|listed| listed.topic == topic)
+            .map(|listed| listed.topic.as_str())
+    }
+
+    /// The push types the certificate can send, judged by the topics it
+    /// lists. The `location` and `liveactivity` push types need token-based
+    /// authentication and are never included.
+    pub fn push_types(&self) -> Vec<PushType> {
+        [
+            PushType::Alert,
+            PushType::Background,
+            PushType::Voip,
+            PushType::Complication,
+            PushType::Fileprovider,
+            PushType::Pushtotalk,
+            PushType::Widgets,
+            PushType::Controls,
+        ]
+        .into_iter()
+        .filter(|push_type| self.topic_for(*push_type).is_some())
+        .collect()
+    }
+
+    /// Whether the certificate is valid for the environment of the endpoint.
+    /// Custom endpoints accept any certificate.
+    pub fn supports_endpoint(&self, endpoint: &Endpoint) -> bool {
+        match endpoint {
+            Endpoint::Production => self.production,
+            Endpoint::Development => self.development,
+            Endpoint::Custom(_) => true,
+        }
+    }
+
+    /// Whether the certificate is past the end of its validity period.
+    pub fn is_expired(&self) -> bool {
+        self.not_after <= OffsetDateTime::now_utc()
+    }
+
+    /// Whether the certificate expires within the given duration from now,
+    /// or already has.
+    pub fn expires_within(&self, duration: Duration) -> bool {
+        self.not_after <= OffsetDateTime::now_utc() + duration
+    }
+}
+
+/// Rejects a client certificate that names its environments if the endpoint
+/// isn't one of them. Certificates that can't be inspected are left for the
+/// TLS handshake to judge.
+pub(crate) fn check_client_certificate(cert_pem: &[u8], endpoint: &Endpoint) -> Result<(), Error> {
+    let info = match CertificateInfo::from_pem(cert_pem) {
+        Ok(info) => info,
+        Err(_) => return Ok(()),
+    };
+
+    #[cfg(feature = "tracing")]
+    {
+        if info.expires_within(EXPIRY_WARNING) {
+            tracing::warn!(not_after = %info.not_after, "the client certificate expires soon");
+        }
+    }
+
+    if (info.development || info.production) && !info.supports_endpoint(endpoint) {
+        let environment = match endpoint {
+            Endpoint::Development => "development",
+            _ => "production",
+        };
+
+        return Err(Error::ClientConfigError(format!(
+            "Invalid client certificate: not valid for the {} environment",
+            environment
+        )));
+    }
+
+    Ok(())
+}
+
+/// Parses the value of the topics extension: a sequence of topics, each
+/// followed by a sequence of its kinds.
+fn parse_topics(value: &[u8]) -> Result<Vec<CertificateTopic>, Error> {
+    let (_, sequence) = parse_der(value).map_err(invalid)?;
+    let mut items = sequence.as_sequence().map_err(invalid)?.iter().peekable();
+    let mut topics = Vec::new();
+
+    while let Some(topic) = items.next() {
+        let topic = topic.as_str().map_err(invalid)?.to_string();
+
+        let kinds = match items.peek().map(|kinds| kinds.as_sequence()) {
+            Some(Ok(kinds)) => {
+                items.next();
+                kinds
+                    .iter()
+                    .map(BerObject::as_str)
+                    .map(|kind| kind.map(String::from))
+                    .collect::<Result<_, _>>()
+                    .map_err(invalid)?
+            }
+            _ => Vec::new(),
+        };
+
+        topics.push(CertificateTopic { topic, kinds });
+    }
+
+    Ok(topics)
+}
+
+fn subject_attribute(certificate: &X509Certificate, oid: &str) -> Option<String> {
+    certificate
+        .subject()
+        .iter_attributes()
+        .find(|attribute| attribute.attr_type().to_id_string() == oid)
+        .and_then(|attribute| attribute.as_str().ok())
+        .map(String::from)
+}
+
+fn invalid<E: std::fmt::Display>(e: E) -> Error {
+    Error::ClientConfigError(format!("Invalid client certificate: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDENTITY_PEM: &[u8] = include_bytes!("../../testdata/identity.pem");
+    const IDENTITY_PKCS12: &[u8] = include_bytes!("../../testdata/identity.p12");
+    const DEVELOPMENT_PEM: &[u8] = include_bytes!("../../testdata/development.pem");
+
+    #[test]
+    fn test_inspect_certificate() {
+        let info = CertificateInfo::from_pem(IDENTITY_PEM).unwrap();
+
+        assert_eq!(Some("com.example.myapp"), info.bundle_id.as_deref());
+        assert_eq!(Some("ASDFQWERTY"), info.team_id.as_deref());
+        assert!(info.development);
+        assert!(info.production);
+        assert_eq!(2126, info.not_after.year());
+        assert!(!info.is_expired());
+
+        assert_eq!(
+            CertificateTopic {
+                topic: "com.example.myapp.voip".into(),
+                kinds: vec!["voip".into()],
+            },
+            info.topics[1]
+        );
+
+        assert_eq!(info, CertificateInfo::from_pkcs12(IDENTITY_PKCS12, "secret").unwrap());
+    }
+
+    #[test]
+    fn test_topic_for_push_type() {
+        let info = CertificateInfo::from_pem(IDENTITY_PEM).unwrap();
+
+        assert_eq!(Some("com.example.myapp"), info.topic_for(PushType::Alert));
+        assert_eq!(Some("com.example.myapp.voip"), info.topic_for(PushType::Voip));
+        assert_eq!(None, info.topic_for(PushType::Fileprovider));
+        assert_eq!(
+            vec![
+                PushType::Alert,
+                PushType::Background,
+                PushType::Voip,
+                PushType::Complication
+            ],
+            info.push_types()
+        );
+    }
+
+    #[test]
+    fn test_supports_endpoint() {
+        let info = CertificateInfo {
+            production: false,
+            ..CertificateInfo::from_pem(IDENTITY_PEM).unwrap()
+        };
+
+        assert!(info.supports_endpoint(&Endpoint::Development));
+        assert!(!info.supports_endpoint(&Endpoint::Production));
+        assert!(info.supports_endpoint(&Endpoint::Custom("http://localhost/3/device/".parse().unwrap())));
+    }
+
+    #[test]
+    fn test_inspect_development_certificate() {
+        let info = CertificateInfo::from_pem(DEVELOPMENT_PEM).unwrap();
+
+        assert!(info.development);
+        assert!(!info.production);
+        assert!(info.topics.is_empty());
+        assert_eq!(Some("com.example.myapp"), info.topic_for(PushType::Alert));
+        assert_eq!(None, info.topic_for(PushType::Voip));
+    }
+
+    #[test]
+    fn test_check_client_certificate() {
+        assert!(check_client_certificate(IDENTITY_PEM, &Endpoint::Production).is_ok());
+        assert!(check_client_certificate(DEVELOPMENT_PEM, &Endpoint::Development).is_ok());
+        assert!(check_client_certificate(b"not a certificate", &Endpoint::Production).is_ok());
+
+        match check_client_certificate(DEVELOPMENT_PEM, &Endpoint::Production) {
+            Err(Error::ClientConfigError(message)) => assert!(message.contains("production environment")),
+            _ => panic!("expected the development certificate to be rejected"),
+        }
+    }
+
+    #[test]
+    fn test_inspect_invalid_certificate() {
+        assert!(matches!(
+            CertificateInfo::from_pem(b"not a certificate"),
+            Err(Error::ClientConfigError(_))
+        ));
+    }
+}
@@ -0,0 +1,281 @@
+//! Broadcast push notifications and channel management
+
+use http::header::{self, HeaderMap, HeaderValue};
+use hyper::{Body, Method};
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use uuid::Uuid;
+
+use crate::{
+    error::Error,
+    request::{PreparedNotification, Request},
+    response::Response,
+};
+
+use super::{
+    client::{header_string, Client, SerializedRequest},
+    header::{APNS_CHANNEL_ID, APNS_REQUEST_ID},
+    PushType,
+};
+
+/// The only push type broadcast channels support, as named by the channel
+/// management API.
+const CHANNEL_PUSH_TYPE: &str = "LiveActivity";
+
+/// Whether APNs keeps the latest broadcast notification of a channel for
+/// devices that are offline when it is sent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize_repr, Serialize_repr)]
+#[repr(u8)]
+pub enum MessageStoragePolicy {
+    /// Notifications are delivered only to devices that are online.
+    #[default]
+    NoMessageStored = 0,
+
+    /// The most recent notification is stored and delivered to devices when
+    /// they come online.
+    MostRecentMessageStored = 1,
+}
+
+/// A broadcast channel of an app.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Channel {
+    /// The base64-encoded channel ID, used as the `apns-channel-id`.
+    pub channel_id: String,
+
+    /// Whether APNs stores the latest notification of the channel.
+    pub message_storage_policy: MessageStoragePolicy,
+
+    /// The `apns-request-id` of the request that created or read the
+    /// channel.
+    pub request_id: Option<String>,
+}
+
+/// The channel configuration sent to and returned by the channel management
+/// API.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct ChannelConfig {
+    message_storage_policy: MessageStoragePolicy,
+    push_type: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChannelList {
+    channels: Vec<String>,
+}
+
+/// Sends broadcast push notifications to Live Activities subscribed to a
+/// channel, and creates, reads and deletes the channels of an app.
+///
+/// Created with [Client::channels], it shares the connections, the signer,
+/// the request timeout and the retry policy of the client. Every request
+/// carries a new `apns-request-id`, which is kept when the request is
+/// retried.
+#[derive(Debug, Clone)]
+pub struct ChannelClient {
+    client: Client,
+    bundle_id: String,
+}
+
+impl ChannelClient {
+    pub(crate) fn new<S>(client: Client, bundle_id: S) -> ChannelClient
+    where
+        S: Into<String>,
+    {
+        ChannelClient {
+            client,
+            bundle_id: bundle_id.into(),
+        }
+    }
+
+    /// The bundle ID of the app the channels belong to.
+    pub fn bundle_id(&self) -> &str {
+        &self.bundle_id
+    }
+
+    /// Send a broadcast push notification to every Live Activity subscribed
+    /// to the channel.
+    ///
+    /// The request must have the
+    /// [Liveactivity](../header/enum.PushType.html#variant.Liveactivity) push
+    /// type, its `device_token` is ignored. The payload is checked against
+    /// its size limit, or truncated, like with
+    /// [Client::send](../client/struct.Client.html#method.send).
+    pub async fn send<T>(&self, channel_id: &str, req: Request<T>) -> Result<Response, Error>
+    where
+        T: Serialize,
+    {
+        if req.push_type != PushType::Liveactivity {
+            return Err(Error::InvalidOptions(
+                "Broadcast push notifications require the liveactivity push type".to_string(),
+            ));
+        }
+
+        let mut notification = self.client.serialize_notification(req)?;
+        notification.headers.extend(request_headers(Some(channel_id))?);
+
+        let request = SerializedRequest {
+            method: Method::POST,
+            uri: self
+                .client
+                .endpoint()
+                .broadcast_url()?
+                .join(&self.bundle_id)?
+                .to_string(),
+            notification,
+            signer: None,
+        };
+
+        self.client.send_request(&request).await
+    }
+
+    /// Create a channel with the given message storage policy.
+    pub async fn create_channel(&self, message_storage_policy: MessageStoragePolicy) -> Result<Channel, Error> {
+        let config = ChannelConfig {
+            message_storage_policy,
+            push_type: CHANNEL_PUSH_TYPE.to_string(),
+        };
+
+        let response = self
+            .manage(Method::POST, "channels", None, serde_json::to_vec(&config)?)
+            .await?;
+
+        let channel_id = header_string(response.headers(), &APNS_CHANNEL_ID)
+            .ok_or_else(|| Error::MissingResponseHeader(APNS_CHANNEL_ID.clone()))?;
+
+        Ok(Channel {
+            channel_id,
+            message_storage_policy,
+            request_id: header_string(response.headers(), &APNS_REQUEST_ID),
+        })
+    }
+
+    /// Read the configuration of a channel.
+    pub async fn read_channel(&self, channel_id: &str) -> Result<Channel, Error> {
+        let response = self
+            .manage(Method::GET, "channels", Some(channel_id), Vec::new())
+            .await?;
+        let request_id = header_string(response.headers(), &APNS_REQUEST_ID);
+
+        let body = hyper::body::to_bytes(response).await?;
+        let config: ChannelConfig = serde_json::from_slice(&body)?;
+
+        Ok(Channel {
+            channel_id: channel_id.to_string(),
+            message_storage_policy: config.message_storage_policy,
+            request_id,
+        })
+    }
+
+    /// List the IDs of all channels of the app.
+    pub async fn list_channels(&self) -> Result<Vec<String>, Error> {
+        let response = self.manage(Method::GET, "all-channels", None, Vec::new()).await?;
+
+        let body = hyper::body::to_bytes(response).await?;
+        let list: ChannelList = serde_json::from_slice(&body)?;
+
+        Ok(list.channels)
+    }
+
+    /// Delete a channel. Live Activities subscribed to it no longer receive
+    /// its notifications.
+    pub async fn delete_channel(&self, channel_id: &str) -> Result<Response, Error> {
+        let request = self.management_request(Method::DELETE, "channels", Some(channel_id), Vec::new())?;
+
+        self.client.send_request(&request).await
+    }
+
+    async fn manage(
+        &self,
+        method: Method,
+        path: &str,
+        channel_id: Option<&str>,
+        body: Vec<u8>,
+    ) -> Result<hyper::Response<Body>, Error> {
+        let request = self.management_request(method, path, channel_id, body)?;
+
+        self.client.execute(&request).await
+    }
+
+    fn management_request(
+        &self,
+        method: Method,
+        path: &str,
+        channel_id: Option<&str>,
+        body: Vec<u8>,
+    ) -> Result<SerializedRequest, Error> {
+        let mut headers = request_headers(channel_id)?;
+
+        if !body.is_empty() {
+            headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        }
+
+        let uri = self
+            .client
+            .endpoint()
+            .channel_url()?
+            .join(&format!("{}/{}", self.bundle_id, path))?;
+
+        Ok(SerializedRequest {
+            method,
+            uri: uri.to_string(),
+            notification: PreparedNotification {
+                headers,
+                body: body.into(),
+                truncated_bytes: 0,
+            },
+            signer: None,
+        })
+    }
+}
+
+/// A new `apns-request-id` and the `apns-channel-id`, if any.
+fn request_headers(channel_id: Option<&str>) -> Result<HeaderMap, Error> {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        APNS_REQUEST_ID.clone(),
+        Uuid::new_v4().hyphenated().to_string().parse()?,
+    );
+
+    if let Some(channel_id) = channel_id {
+        headers.insert(APNS_CHANNEL_ID.clone(), channel_id.parse()?);
+    }
+
+    Ok(headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_channel_config() {
+        let config = ChannelConfig {
+            message_storage_policy: MessageStoragePolicy::MostRecentMessageStored,
+            push_type: CHANNEL_PUSH_TYPE.to_string(),
+        };
+
+        assert_eq!(
+            json!({ "message-storage-policy": 1, "push-type": "LiveActivity" }),
+            serde_json::to_value(&config).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_channel_urls() {
+        let client = crate::client::ClientBuilder::new().build().unwrap();
+        let channels = client.channels("com.example.myapp");
+
+        let request = channels
+            .management_request(Method::GET, "all-channels", None, Vec::new())
+            .unwrap();
+
+        assert_eq!(
+            "https://api-manage-broadcast.push.apple.com:2196/1/apps/com.example.myapp/all-channels",
+            request.uri
+        );
+        assert!(request.notification.headers.contains_key(&APNS_REQUEST_ID));
+        assert!(!request.notification.headers.contains_key(header::CONTENT_TYPE));
+    }
+}
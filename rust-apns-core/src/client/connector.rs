@@ -0,0 +1,494 @@
+//! The transport used by the client to reach APNs
+
+use arc_swap::ArcSwap;
+use hyper::{
+    client::{
+        connect::{Connected, Connection, HttpConnector},
+        Builder as HttpBuilder,
+    },
+    service::Service,
+    Client as HttpClient, Uri,
+};
+use std::{
+    fmt,
+    future::Future,
+    io,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::TcpStream,
+};
+
+#[cfg(all(feature = "openssl", not(feature = "ring")))]
+use openssl::{
+    pkey::PKey,
+    ssl::{SslConnector, SslMethod},
+    x509::X509,
+};
+#[cfg(feature = "ring")]
+use rustls::{Certificate, ClientConfig, OwnedTrustAnchor, PrivateKey, RootCertStore, ServerName};
+#[cfg(all(feature = "openssl", not(feature = "ring")))]
+use tokio_openssl::SslStream;
+#[cfg(feature = "ring")]
+use tokio_rustls::{client::TlsStream as RustlsStream, TlsConnector};
+
+use super::{
+    builder::CertificateAuthority,
+    streams::{SettingsReader, StreamLimiter},
+};
+use crate::error::Error;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+#[cfg(all(feature = "openssl", not(feature = "ring")))]
+type TlsStream = SslStream<TcpStream>;
+
+#[cfg(feature = "ring")]
+type TlsStream = RustlsStream<TcpStream>;
+
+/// The PEM encoded certificate and private key presented to APNs for
+/// certificate-based authentication. Both may point to the same combined PEM.
+#[derive(Clone, Copy)]
+pub(crate) struct ClientIdentity<'a> {
+    pub(crate) cert_pem: &'a [u8],
+    pub(crate) key_pem: &'a [u8],
+}
+
+/// The connection pool of a client, replaced as a whole when the client
+/// certificate is reloaded.
+///
+/// Requests take the current pool when they are sent. Replacing it makes new
+/// requests open new connections with the new certificate, while the
+/// requests in flight finish on the old connections, which close once they
+/// are done.
+pub(crate) struct Transport {
+    http_client: ArcSwap<HttpClient<Connector>>,
+    http_builder: HttpBuilder,
+    ca: Option<OwnedCertificateAuthority>,
+    connect_timeout: Option<Duration>,
+    limiter: Arc<StreamLimiter>,
+}
+
+/// A copy of the [CertificateAuthority], kept to connect again.
+enum OwnedCertificateAuthority {
+    Pem(Vec<u8>),
+    Der(Vec<u8>),
+}
+
+impl Transport {
+    pub(crate) fn new(
+        http_builder: HttpBuilder,
+        ca: Option<&CertificateAuthority>,
+        identity: Option<ClientIdentity>,
+        connect_timeout: Option<Duration>,
+        max_concurrent_streams: usize,
+    ) -> Result<Self, Error> {
+        let limiter = Arc::new(StreamLimiter::new(max_concurrent_streams));
+        let connector = Connector::new(ca, identity, connect_timeout, limiter.clone())?;
+
+        let ca = ca.map(|ca| match *ca {
+            CertificateAuthority::Pem(pem) => OwnedCertificateAuthority::Pem(pem.to_vec()),
+            CertificateAuthority::Der(der) => OwnedCertificateAuthority::Der(der.to_vec()),
+        });
+
+        Ok(Self {
+            http_client: ArcSwap::from_pointee(http_builder.build(connector)),
+            http_builder,
+            ca,
+            connect_timeout,
+            limiter,
+        })
+    }
+
+    /// Limits the requests in flight to the streams the server allows.
+    pub(crate) fn limiter(&self) -> &StreamLimiter {
+        &self.limiter
+    }
+
+    /// The current connection pool.
+    pub(crate) fn http_client(&self) -> Arc<HttpClient<Connector>> {
+        self.http_client.load_full()
+    }
+
+    /// Replaces the connection pool with one presenting the given client
+    /// certificate. If the certificate is not valid the current pool is kept.
+    pub(crate) fn reload(&self, identity: Option<ClientIdentity>) -> Result<(), Error> {
+        let ca = self.ca.as_ref().map(|ca| match ca {
+            OwnedCertificateAuthority::Pem(pem) => CertificateAuthority::Pem(pem),
+            OwnedCertificateAuthority::Der(der) => CertificateAuthority::Der(der),
+        });
+
+        let connector = Connector::new(ca.as_ref(), identity, self.connect_timeout, self.limiter.clone())?;
+        self.http_client.store(Arc::new(self.http_builder.build(connector)));
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Transport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Transport")
+            .field("connect_timeout", &self.connect_timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Connects with TLS and ALPN `h2` to `https` endpoints. Custom `http`
+/// endpoints, such as local mock servers, are connected to over plain TCP.
+#[derive(Clone)]
+pub(crate) struct Connector {
+    #[cfg(all(feature = "openssl", not(feature = "ring")))]
+    tls: SslConnector,
+    #[cfg(feature = "ring")]
+    tls: TlsConnector,
+    http: HttpConnector,
+    connect_timeout: Option<Duration>,
+    limiter: Arc<StreamLimiter>,
+}
+
+impl Connector {
+    /// Create a connector trusting the system roots and the given certificate
+    /// authority, optionally presenting a client certificate.
+    #[cfg(all(feature = "openssl", not(feature = "ring")))]
+    pub(crate) fn new(
+        ca: Option<&CertificateAuthority>,
+        identity: Option<ClientIdentity>,
+        connect_timeout: Option<Duration>,
+        limiter: Arc<StreamLimiter>,
+    ) -> Result<Self, Error> {
+        let mut builder =
+            SslConnector::builder(SslMethod::tls_client()).map_err(config_error("Invalid TLS settings"))?;
+        builder
+            .set_alpn_protos(b"\x02h2")
+            .map_err(config_error("Invalid TLS settings"))?;
+
+        if let Some(ca) = ca {
+            let certificates = match ca {
+                CertificateAuthority::Pem(pem) => X509::stack_from_pem(pem),
+                CertificateAuthority::Der(der) => X509::from_der(der).map(|certificate| vec![certificate]),
+            }
+            .map_err(config_error("Invalid CA certificate"))?;
+
+            if certificates.is_empty() {
+                return Err(Error::ClientConfigError(
+                    "Invalid CA certificate: no certificate found".into(),
+                ));
+            }
+
+            for certificate in certificates {
+                builder
+                    .cert_store_mut()
+                    .add_cert(certificate)
+                    .map_err(config_error("Invalid CA certificate"))?;
+            }
+        }
+
+        if let Some(identity) = identity {
+            let mut chain = X509::stack_from_pem(identity.cert_pem)
+                .map_err(config_error("Invalid client certificate"))?
+                .into_iter();
+
+            let certificate = chain
+                .next()
+                .ok_or_else(|| Error::ClientConfigError("Invalid client certificate: no certificate found".into()))?;
+
+            let key =
+                PKey::private_key_from_pem(identity.key_pem).map_err(config_error("Invalid client private key"))?;
+
+            builder
+                .set_certificate(&certificate)
+                .map_err(config_error("Invalid client certificate"))?;
+
+            for intermediate in chain {
+                builder
+                    .add_extra_chain_cert(intermediate)
+                    .map_err(config_error("Invalid client certificate chain"))?;
+            }
+
+            builder
+                .set_private_key(&key)
+                .and_then(|_| builder.check_private_key())
+                .map_err(config_error("Invalid client private key"))?;
+        }
+
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+
+        Ok(Self {
+            tls: builder.build(),
+            http,
+            connect_timeout,
+            limiter,
+        })
+    }
+
+    /// Create a connector trusting the Mozilla root certificates and the
+    /// given certificate authority, optionally presenting a client
+    /// certificate.
+    #[cfg(feature = "ring")]
+    pub(crate) fn new(
+        ca: Option<&CertificateAuthority>,
+        identity: Option<ClientIdentity>,
+        connect_timeout: Option<Duration>,
+        limiter: Arc<StreamLimiter>,
+    ) -> Result<Self, Error> {
+        let mut roots = RootCertStore::empty();
+        roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|anchor| {
+            OwnedTrustAnchor::from_subject_spki_name_constraints(anchor.subject, anchor.spki, anchor.name_constraints)
+        }));
+
+        if let Some(ca) = ca {
+            let certificates = match ca {
+                CertificateAuthority::Pem(mut pem) => {
+                    rustls_pemfile::certs(&mut pem).map_err(config_error("Invalid CA certificate"))?
+                }
+                CertificateAuthority::Der(der) => vec![der.to_vec()],
+            };
+
+            if certificates.is_empty() {
+                return Err(Error::ClientConfigError(
+                    "Invalid CA certificate: no certificate found".into(),
+                ));
+            }
+
+            for certificate in certificates {
+                roots
+                    .add(&Certificate(certificate))
+                    .map_err(config_error("Invalid CA certificate"))?;
+            }
+        }
+
+        let builder = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots);
+
+        let mut config = match identity {
+            Some(identity) => {
+                let chain: Vec<Certificate> = rustls_pemfile::certs(&mut &identity.cert_pem[..])
+                    .map_err(config_error("Invalid client certificate"))?
+                    .into_iter()
+                    .map(Certificate)
+                    .collect();
+
+                if chain.is_empty() {
+                    return Err(Error::ClientConfigError(
+                        "Invalid client certificate: no certificate found".into(),
+                    ));
+                }
+
+                let key = read_private_key(identity.key_pem)?;
+
+                builder
+                    .with_client_auth_cert(chain, key)
+                    .map_err(config_error("Invalid client private key"))?
+            }
+            None => builder.with_no_client_auth(),
+        };
+
+        config.alpn_protocols = vec![b"h2".to_vec()];
+
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+
+        Ok(Self {
+            tls: TlsConnector::from(Arc::new(config)),
+            http,
+            connect_timeout,
+            limiter,
+        })
+    }
+
+    #[cfg(all(feature = "openssl", not(feature = "ring")))]
+    fn connect_tls(&mut self, dst: Uri) -> impl Future<Output = Result<TlsStream, BoxError>> + Send + 'static {
+        let tls = self.tls.clone();
+        let host = dst.host().unwrap_or_default().to_string();
+        let connecting = self.http.call(dst);
+
+        async move {
+            let tcp = connecting.await?;
+            let ssl = tls.configure()?.into_ssl(&host)?;
+
+            let mut stream = SslStream::new(ssl, tcp)?;
+            Pin::new(&mut stream).connect().await?;
+
+            Ok(stream)
+        }
+    }
+
+    #[cfg(feature = "ring")]
+    fn connect_tls(&mut self, dst: Uri) -> impl Future<Output = Result<TlsStream, BoxError>> + Send + 'static {
+        let tls = self.tls.clone();
+        let host = dst.host().unwrap_or_default().to_string();
+        let connecting = self.http.call(dst);
+
+        async move {
+            let tcp = connecting.await?;
+            let server_name = ServerName::try_from(host.as_str())?;
+
+            Ok(tls.connect(server_name, tcp).await?)
+        }
+    }
+}
+
+/// Reads the first PKCS8, PKCS1 or SEC1 private key of a PEM file.
+#[cfg(feature = "ring")]
+fn read_private_key(mut pem: &[u8]) -> Result<PrivateKey, Error> {
+    for item in rustls_pemfile::read_all(&mut pem).map_err(config_error("Invalid client private key"))? {
+        match item {
+            rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::ECKey(key) => return Ok(PrivateKey(key)),
+            _ => continue,
+        }
+    }
+
+    Err(Error::ClientConfigError(
+        "Invalid client private key: no private key found".into(),
+    ))
+}
+
+fn config_error<E: fmt::Display>(context: &'static str) -> impl Fn(E) -> Error {
+    move |e| Error::ClientConfigError(format!("{}: {}", context, e))
+}
+
+impl fmt::Debug for Connector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Connector")
+            .field("connect_timeout", &self.connect_timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Service<Uri> for Connector {
+    type Response = Stream;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Stream, BoxError>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, dst: Uri) -> Self::Future {
+        let limiter = self.limiter.clone();
+
+        let connecting: Self::Future = if dst.scheme_str() == Some("http") {
+            let connecting = self.http.call(dst);
+            Box::pin(async move { Ok(Stream::new(Io::Plain(connecting.await?), limiter)) })
+        } else {
+            let connecting = self.connect_tls(dst);
+            Box::pin(async move { Ok(Stream::new(Io::Tls(Box::new(connecting.await?)), limiter)) })
+        };
+
+        match self.connect_timeout {
+            Some(timeout) => Box::pin(async move {
+                match tokio::time::timeout(timeout, connecting).await {
+                    Ok(result) => result,
+                    Err(_) => Err(io::Error::new(io::ErrorKind::TimedOut, "connecting to APNs timed out").into()),
+                }
+            }),
+            None => connecting,
+        }
+    }
+}
+
+/// A connection opened by the [Connector].
+pub(crate) struct Stream {
+    io: Io,
+    /// Reads the settings of an HTTP/2 server.
+    settings: Option<SettingsReader>,
+}
+
+enum Io {
+    Tls(Box<TlsStream>),
+    Plain(TcpStream),
+}
+
+impl Stream {
+    fn new(io: Io, limiter: Arc<StreamLimiter>) -> Stream {
+        let settings = if io.is_h2() {
+            Some(SettingsReader::new(limiter))
+        } else {
+            limiter.remove_server_limit();
+            None
+        };
+
+        Stream { io, settings }
+    }
+}
+
+impl Io {
+    /// Whether the connection speaks HTTP/2: negotiated with ALPN over TLS,
+    /// and always over plain TCP unless HTTP/1.1 is allowed.
+    fn is_h2(&self) -> bool {
+        match self {
+            #[cfg(all(feature = "openssl", not(feature = "ring")))]
+            Io::Tls(stream) => stream.ssl().selected_alpn_protocol() == Some(b"h2"),
+            #[cfg(feature = "ring")]
+            Io::Tls(stream) => stream.get_ref().1.alpn_protocol() == Some(b"h2"),
+            Io::Plain(_) => !cfg!(feature = "http1"),
+        }
+    }
+}
+
+impl Connection for Stream {
+    fn connected(&self) -> Connected {
+        let connected = match self.io {
+            #[cfg(all(feature = "openssl", not(feature = "ring")))]
+            Io::Tls(ref stream) => stream.get_ref().connected(),
+            #[cfg(feature = "ring")]
+            Io::Tls(ref stream) => stream.get_ref().0.connected(),
+            Io::Plain(ref stream) => return stream.connected(),
+        };
+
+        if self.io.is_h2() {
+            connected.negotiated_h2()
+        } else {
+            connected
+        }
+    }
+}
+
+impl AsyncRead for Stream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let filled = buf.filled().len();
+
+        let read = match this.io {
+            Io::Tls(ref mut stream) => Pin::new(stream).poll_read(cx, buf),
+            Io::Plain(ref mut stream) => Pin::new(stream).poll_read(cx, buf),
+        };
+
+        if let (Poll::Ready(Ok(())), Some(settings)) = (&read, &mut this.settings) {
+            settings.read(&buf.filled()[filled..]);
+        }
+
+        read
+    }
+}
+
+impl AsyncWrite for Stream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut().io {
+            Io::Tls(ref mut stream) => Pin::new(stream).poll_write(cx, buf),
+            Io::Plain(ref mut stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut().io {
+            Io::Tls(ref mut stream) => Pin::new(stream).poll_flush(cx),
+            Io::Plain(ref mut stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut().io {
+            Io::Tls(ref mut stream) => Pin::new(stream).poll_shutdown(cx),
+            Io::Plain(ref mut stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
@@ -4,12 +4,23 @@ use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use url::Url;
 
-static PRODUCTION_SERVER: Lazy<Url> =
-    Lazy::new(|| Url::parse("https://api.push.apple.com/3/device/").unwrap());
+static PRODUCTION_SERVER: Lazy<Url> = Lazy::new(|| Url::parse("https://api.push.apple.com/3/device/").unwrap());
 
 static DEVELOPMENT_SERVER: Lazy<Url> =
     Lazy::new(|| Url::parse("https://api.sandbox.push.apple.com/3/device/").unwrap());
 
+static PRODUCTION_BROADCAST_SERVER: Lazy<Url> =
+    Lazy::new(|| Url::parse("https://api.push.apple.com/4/broadcasts/apps/").unwrap());
+
+static DEVELOPMENT_BROADCAST_SERVER: Lazy<Url> =
+    Lazy::new(|| Url::parse("https://api.sandbox.push.apple.com/4/broadcasts/apps/").unwrap());
+
+static PRODUCTION_CHANNEL_SERVER: Lazy<Url> =
+    Lazy::new(|| Url::parse("https://api-manage-broadcast.push.apple.com:2196/1/apps/").unwrap());
+
+static DEVELOPMENT_CHANNEL_SERVER: Lazy<Url> =
+    Lazy::new(|| Url::parse("https://api-manage-broadcast.sandbox.push.apple.com:2195/1/apps/").unwrap());
+
 /// Apple Push Notification service endpoint.
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub enum Endpoint {
@@ -26,6 +37,27 @@ impl Endpoint {
             Self::Custom(url) => url,
         }
     }
+
+    /// The base URL for broadcast push notifications, to be joined with the
+    /// bundle ID. For a custom endpoint this is `/4/broadcasts/apps/` on the
+    /// same host.
+    pub fn broadcast_url(&self) -> Result<Url, url::ParseError> {
+        match self {
+            Self::Production => Ok(PRODUCTION_BROADCAST_SERVER.clone()),
+            Self::Development => Ok(DEVELOPMENT_BROADCAST_SERVER.clone()),
+            Self::Custom(url) => url.join("/4/broadcasts/apps/"),
+        }
+    }
+
+    /// The base URL of the channel management API, to be joined with the
+    /// bundle ID. For a custom endpoint this is `/1/apps/` on the same host.
+    pub fn channel_url(&self) -> Result<Url, url::ParseError> {
+        match self {
+            Self::Production => Ok(PRODUCTION_CHANNEL_SERVER.clone()),
+            Self::Development => Ok(DEVELOPMENT_CHANNEL_SERVER.clone()),
+            Self::Custom(url) => url.join("/1/apps/"),
+        }
+    }
 }
 
 impl Debug for Endpoint {
@@ -66,8 +98,7 @@ impl<'de> Deserialize<'de> for Endpoint {
         use serde::de::{Error, Unexpected};
 
         let s = String::deserialize(deserializer)?;
-        Endpoint::from_str(&s)
-            .map_err(|err| Error::invalid_value(Unexpected::Str(&s), &err.to_string().as_str()))
+        Endpoint::from_str(&s).map_err(|err| Error::invalid_value(Unexpected::Str(&s), &err.to_string().as_str()))
     }
 }
 
@@ -70,6 +70,16 @@ pub static APNS_TOPIC: HeaderName = HeaderName::from_static("apns-topic");
 /// the requests. The value of this key must not exceed 64 bytes.
 pub static APNS_COLLAPSE_ID: HeaderName = HeaderName::from_static("apns-collapse-id");
 
+/// The base64-encoded identifier of a broadcast channel. Sent with broadcast
+/// push notifications and with the requests to read or delete a channel, and
+/// returned by APNs when a channel is created.
+pub static APNS_CHANNEL_ID: HeaderName = HeaderName::from_static("apns-channel-id");
+
+/// A canonical UUID that identifies a request to the broadcast or channel
+/// management API. If you omit this header, APNs creates a UUID for you.
+/// APNs returns the value in its response either way.
+pub static APNS_REQUEST_ID: HeaderName = HeaderName::from_static("apns-request-id");
+
 /// Use the `alert` push type for notifications that trigger a user
 /// interaction—for example, an alert, badge, or sound. If you set this push
 /// type, the `apns-topic` header field must use your app’s bundle ID as the
@@ -156,6 +166,40 @@ pub static FILEPROVIDER: HeaderValue = HeaderValue::from_static("fileprovider");
 /// iOS, tvOS, and iPadOS.
 pub static MDM: HeaderValue = HeaderValue::from_static("mdm");
 
+/// Use the `liveactivity` push type for notifications that start, update or
+/// end a Live Activity. If you set this push type, the `apns-topic` header
+/// field must use your app’s bundle ID with `.push-type.liveactivity` appended
+/// to the end. For more information, see [Starting and updating Live
+/// Activities with ActivityKit push
+/// notifications](https://developer.apple.com/documentation/activitykit/starting-and-updating-live-activities-with-activitykit-push-notifications).
+///
+/// The `liveactivity` push type is available on iOS and iPadOS 16.1 and
+/// later, and only supports token-based authentication.
+pub static LIVEACTIVITY: HeaderValue = HeaderValue::from_static("liveactivity");
+
+/// Use the `pushtotalk` push type for notifications that provide information
+/// about incoming Push to Talk (PTT) audio. If you set this push type, the
+/// `apns-topic` header field must use your app’s bundle ID with `.voip-ptt`
+/// appended to the end. Always use priority 10 and an expiration of 0.
+///
+/// The `pushtotalk` push type is available on iOS 16 and later.
+pub static PUSHTOTALK: HeaderValue = HeaderValue::from_static("pushtotalk");
+
+/// Use the `widgets` push type for notifications that reload the widgets of
+/// your app. If you set this push type, the `apns-topic` header field must use
+/// your app’s bundle ID with `.push-type.widgets` appended to the end. The
+/// payload must contain `content-changed` set to `true`.
+pub static WIDGETS: HeaderValue = HeaderValue::from_static("widgets");
+
+/// Use the `controls` push type for notifications that reload the controls
+/// of your app in Control Center and on the Lock Screen. If you set this push
+/// type, the `apns-topic` header field must use your app’s bundle ID with
+/// `.push-type.controls` appended to the end. The payload must contain
+/// `content-changed` set to `true`.
+///
+/// The `controls` push type is available on iOS and iPadOS 18 and later.
+pub static CONTROLS: HeaderValue = HeaderValue::from_static("controls");
+
 /// Send the notification immediately.
 pub static PRIORITY_IMMEDIATE: HeaderValue = HeaderValue::from_static("10");
 
@@ -258,6 +302,50 @@ pub enum PushType {
     /// The mdm push type is not available on watchOS. It is recommended on
     /// macOS, iOS, tvOS, and iPadOS.
     Mdm,
+
+    /// Use the `liveactivity` push type for notifications that start, update
+    /// or end a Live Activity. If you set this push type, the `apns-topic`
+    /// header field must use your app’s bundle ID with
+    /// `.push-type.liveactivity` appended to the end. For more information,
+    /// see [Starting and updating Live Activities with ActivityKit push
+    /// notifications](https://developer.apple.com/documentation/activitykit/starting-and-updating-live-activities-with-activitykit-push-notifications).
+    ///
+    /// The payload must contain an `event` and a `timestamp`.
+    ///
+    /// The `liveactivity` push type is available on iOS and iPadOS 16.1 and
+    /// later, and only supports token-based authentication.
+    Liveactivity,
+
+    /// Use the `pushtotalk` push type for notifications that provide
+    /// information about incoming Push to Talk (PTT) audio. If you set this
+    /// push type, the `apns-topic` header field must use your app’s bundle ID
+    /// with `.voip-ptt` appended to the end.
+    ///
+    /// Always use priority 10 and an expiration of 0, so the notification is
+    /// delivered right away or not at all.
+    ///
+    /// The `pushtotalk` push type is available on iOS 16 and later.
+    Pushtotalk,
+
+    /// Use the `widgets` push type for notifications that reload the widgets
+    /// of your app. If you set this push type, the `apns-topic` header field
+    /// must use your app’s bundle ID with `.push-type.widgets` appended to the
+    /// end.
+    ///
+    /// The payload must contain `content-changed` set to `true` and no alert,
+    /// badge or sound.
+    Widgets,
+
+    /// Use the `controls` push type for notifications that reload the
+    /// controls of your app in Control Center and on the Lock Screen. If you
+    /// set this push type, the `apns-topic` header field must use your app’s
+    /// bundle ID with `.push-type.controls` appended to the end.
+    ///
+    /// The payload must contain `content-changed` set to `true` and no alert,
+    /// badge or sound.
+    ///
+    /// The `controls` push type is available on iOS and iPadOS 18 and later.
+    Controls,
 }
 
 impl Default for PushType {
@@ -276,6 +364,10 @@ impl From<PushType> for HeaderValue {
             PushType::Complication => COMPLICATION.clone(),
             PushType::Fileprovider => FILEPROVIDER.clone(),
             PushType::Mdm => MDM.clone(),
+            PushType::Liveactivity => LIVEACTIVITY.clone(),
+            PushType::Pushtotalk => PUSHTOTALK.clone(),
+            PushType::Widgets => WIDGETS.clone(),
+            PushType::Controls => CONTROLS.clone(),
         }
     }
 }
@@ -291,6 +383,31 @@ impl PushType {
             PAYLOAD_SIZE_LIMIT
         }
     }
+
+    /// The suffix appended to the bundle ID to form the `apns-topic` of this
+    /// push type, empty if the bundle ID is the topic.
+    ///
+    /// The topic of [Mdm](#variant.Mdm) notifications is the UID attribute of
+    /// the MDM push certificate instead, it can't be derived from a bundle ID.
+    pub fn topic_suffix(&self) -> &'static str {
+        match self {
+            PushType::Alert | PushType::Background | PushType::Mdm => "",
+            PushType::Location => ".location-query",
+            PushType::Voip => ".voip",
+            PushType::Complication => ".complication",
+            PushType::Fileprovider => ".pushkit.fileprovider",
+            PushType::Liveactivity => ".push-type.liveactivity",
+            PushType::Pushtotalk => ".voip-ptt",
+            PushType::Widgets => ".push-type.widgets",
+            PushType::Controls => ".push-type.controls",
+        }
+    }
+
+    /// The `apns-topic` of this push type for the app with the given bundle
+    /// ID, e.g. `com.example.myapp.voip` for [Voip](#variant.Voip).
+    pub fn topic(&self, bundle_id: &str) -> String {
+        format!("{}{}", bundle_id, self.topic_suffix())
+    }
 }
 
 /// Push notification priority.
@@ -326,3 +443,52 @@ impl From<Priority> for HeaderValue {
 
 derive_fromstr_from_deserialize!(Priority);
 derive_display_from_serialize!(Priority);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topics() {
+        let bundle_id = "com.example.myapp";
+
+        assert_eq!("com.example.myapp", PushType::Alert.topic(bundle_id));
+        assert_eq!("com.example.myapp", PushType::Background.topic(bundle_id));
+        assert_eq!("com.example.myapp.location-query", PushType::Location.topic(bundle_id));
+        assert_eq!("com.example.myapp.voip", PushType::Voip.topic(bundle_id));
+        assert_eq!(
+            "com.example.myapp.complication",
+            PushType::Complication.topic(bundle_id)
+        );
+        assert_eq!(
+            "com.example.myapp.pushkit.fileprovider",
+            PushType::Fileprovider.topic(bundle_id)
+        );
+        assert_eq!(
+            "com.example.myapp.push-type.liveactivity",
+            PushType::Liveactivity.topic(bundle_id)
+        );
+        assert_eq!("com.example.myapp.voip-ptt", PushType::Pushtotalk.topic(bundle_id));
+        assert_eq!(
+            "com.example.myapp.push-type.widgets",
+            PushType::Widgets.topic(bundle_id)
+        );
+        assert_eq!(
+            "com.example.myapp.push-type.controls",
+            PushType::Controls.topic(bundle_id)
+        );
+    }
+
+    #[test]
+    fn test_push_type_header_values() {
+        for (push_type, value) in [
+            (PushType::Pushtotalk, "pushtotalk"),
+            (PushType::Widgets, "widgets"),
+            (PushType::Controls, "controls"),
+        ] {
+            assert_eq!(value, HeaderValue::from(push_type));
+            assert_eq!(value, push_type.to_string());
+            assert_eq!(push_type, value.parse().unwrap());
+        }
+    }
+}
@@ -1,7 +1,22 @@
+pub mod builder;
+pub mod certificate;
+pub mod channel;
 pub mod client;
-pub mod header;
+mod connector;
 pub mod endpoint;
+pub mod header;
+pub mod registry;
+pub mod retry;
+pub mod router;
 pub mod signer;
+mod streams;
 
+pub use builder::{Authentication, CertificateAuthority, ClientBuilder};
+pub use certificate::{CertificateInfo, CertificateTopic};
+pub use channel::{Channel, ChannelClient, MessageStoragePolicy};
 pub use endpoint::*;
 pub use header::{Priority, PushType};
+pub use registry::{AppConfig, Credentials, Registry, RegistryConfig};
+pub use retry::RetryPolicy;
+pub use router::{Environment, Routed, RouterClient};
+pub use signer::{MockSigningKey, SigningKey};
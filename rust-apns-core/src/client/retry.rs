@@ -0,0 +1,260 @@
+//! Retrying notifications after transient failures
+
+use rand::Rng;
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use crate::{error::Error, response::Reason};
+
+/// How often and how long the client retries a notification that failed for
+/// a transient reason.
+///
+/// Retried are connection errors, request timeouts, the status codes 429, 500
+/// and 503, whatever their body, and the reasons `TooManyRequests`,
+/// `InternalServerError`, `ServiceUnavailable` and `Shutdown`. Every other
+/// rejection, such as `BadDeviceToken` or
+/// `Unregistered`, is returned right away.
+///
+/// Between attempts the client waits for a random time between zero and an
+/// exponentially growing backoff ("full jitter"). All attempts share the same
+/// `apns-id`, so APNs can tell them apart from new notifications.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first one.
+    pub max_attempts: u32,
+
+    /// The backoff before the first retry.
+    pub initial_backoff: Duration,
+
+    /// The upper bound of the backoff between two attempts.
+    pub max_backoff: Duration,
+
+    /// The time after the first attempt at which no further retry is
+    /// started.
+    pub deadline: Option<Duration>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            deadline: Some(Duration::from_secs(30)),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Calls `attempt` until it succeeds, fails permanently or the policy is
+    /// exhausted, returning the last result.
+    pub(crate) async fn retry<F, Fut, T>(&self, mut attempt: F) -> Result<T, Error>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let started = Instant::now();
+        let mut attempts = 1;
+
+        loop {
+            match attempt().await {
+                Err(error) if attempts < self.max_attempts && is_transient(&error) => {
+                    let backoff = self.backoff(attempts);
+
+                    if let Some(deadline) = self.deadline {
+                        if started.elapsed() + backoff > deadline {
+                            return Err(error);
+                        }
+                    }
+
+                    #[cfg(feature = "tracing")]
+                    {
+                        tracing::debug!(
+                            attempts,
+                            ?backoff,
+                            "Client::send transient failure, retrying: {}",
+                            error
+                        );
+                    }
+
+                    tokio::time::sleep(backoff).await;
+                    attempts += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// A random backoff of up to `initial_backoff * 2^(attempts - 1)`, capped
+    /// at `max_backoff`.
+    fn backoff(&self, attempts: u32) -> Duration {
+        let exponential = self
+            .initial_backoff
+            .checked_mul(1 << attempts.saturating_sub(1).min(31))
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff);
+
+        exponential.mul_f64(rand::thread_rng().gen_range(0.0..=1.0))
+    }
+}
+
+/// Whether sending the same notification again might succeed.
+fn is_transient(error: &Error) -> bool {
+    match error {
+        Error::ConnectionError(_) | Error::TimeoutError => true,
+        // A body that is missing or not understood leaves only the status.
+        Error::ResponseError(response) => {
+            matches!(response.code, 429 | 500 | 503)
+                || matches!(
+                    error.reason(),
+                    Some(
+                        Reason::TooManyRequests
+                            | Reason::InternalServerError
+                            | Reason::ServiceUnavailable
+                            | Reason::Shutdown
+                    )
+                )
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response::Response;
+    use std::cell::Cell;
+
+    fn rejection(reason: Reason) -> Error {
+        Error::ResponseError(Response {
+            error: Some(reason.into()),
+            apns_id: None,
+            request_id: None,
+            code: http::StatusCode::from(reason).as_u16(),
+            truncated_bytes: 0,
+        })
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(2),
+            deadline: None,
+        }
+    }
+
+    #[test]
+    fn test_backoff_is_capped() {
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(250),
+            ..Default::default()
+        };
+
+        for attempts in 1..40 {
+            assert!(policy.backoff(attempts) <= Duration::from_millis(250));
+        }
+
+        assert!(policy.backoff(1) <= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_transient_errors() {
+        assert!(is_transient(&rejection(Reason::TooManyRequests)));
+        assert!(is_transient(&rejection(Reason::InternalServerError)));
+        assert!(is_transient(&rejection(Reason::ServiceUnavailable)));
+        assert!(is_transient(&rejection(Reason::Shutdown)));
+        assert!(is_transient(&Error::TimeoutError));
+
+        for code in [429, 500, 503] {
+            assert!(is_transient(&Error::ResponseError(Response {
+                error: Some(Reason::Unknown.into()),
+                apns_id: None,
+                request_id: None,
+                code,
+                truncated_bytes: 0,
+            })));
+        }
+
+        assert!(!is_transient(&rejection(Reason::BadDeviceToken)));
+        assert!(!is_transient(&rejection(Reason::Unregistered)));
+        assert!(!is_transient(&rejection(Reason::PayloadTooLarge)));
+        assert!(!is_transient(&Error::InvalidRequest(Vec::new())));
+    }
+
+    #[tokio::test]
+    async fn test_retry_until_success() {
+        let attempts = Cell::new(0);
+
+        let result = policy()
+            .retry(|| {
+                attempts.set(attempts.get() + 1);
+                let attempt = attempts.get();
+
+                async move {
+                    match attempt {
+                        1 => Err(rejection(Reason::ServiceUnavailable)),
+                        _ => Ok(attempt),
+                    }
+                }
+            })
+            .await;
+
+        assert_eq!(2, result.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_retry_stops_after_max_attempts() {
+        let attempts = Cell::new(0);
+
+        let result: Result<(), _> = policy()
+            .retry(|| {
+                attempts.set(attempts.get() + 1);
+                async { Err(rejection(Reason::TooManyRequests)) }
+            })
+            .await;
+
+        assert_eq!(Some(Reason::TooManyRequests), result.unwrap_err().reason());
+        assert_eq!(3, attempts.get());
+    }
+
+    #[tokio::test]
+    async fn test_permanent_errors_are_not_retried() {
+        let attempts = Cell::new(0);
+
+        let result: Result<(), _> = policy()
+            .retry(|| {
+                attempts.set(attempts.get() + 1);
+                async { Err(rejection(Reason::Unregistered)) }
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(1, attempts.get());
+    }
+
+    #[tokio::test]
+    async fn test_retry_stops_at_deadline() {
+        let attempts = Cell::new(0);
+
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_secs(60),
+            max_backoff: Duration::from_secs(60),
+            deadline: Some(Duration::from_secs(1)),
+        };
+
+        let result: Result<(), _> = policy
+            .retry(|| {
+                attempts.set(attempts.get() + 1);
+                async { Err(Error::TimeoutError) }
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert!(attempts.get() < 10);
+    }
+}
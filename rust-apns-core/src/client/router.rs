@@ -0,0 +1,218 @@
+//! Routing notifications between the production and development environments
+
+use serde::Serialize;
+
+use crate::{
+    error::Error,
+    request::Request,
+    response::{Reason, Response},
+};
+
+use super::{
+    builder::Authentication,
+    client::{Client, SerializedRequest},
+    endpoint::Endpoint,
+};
+
+/// One of the two APNs environments a device token can be registered in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Environment {
+    /// The environment of App Store and ad hoc builds.
+    #[default]
+    Production,
+
+    /// The sandbox environment of debug builds.
+    Development,
+}
+
+impl Environment {
+    /// The environment this one isn't.
+    pub fn other(self) -> Environment {
+        match self {
+            Environment::Production => Environment::Development,
+            Environment::Development => Environment::Production,
+        }
+    }
+}
+
+impl From<Environment> for Endpoint {
+    fn from(environment: Environment) -> Endpoint {
+        match environment {
+            Environment::Production => Endpoint::Production,
+            Environment::Development => Endpoint::Development,
+        }
+    }
+}
+
+/// The response to a notification sent with [RouterClient::send], and the
+/// environment that accepted it.
+#[derive(Debug)]
+pub struct Routed {
+    /// The environment the notification was delivered to.
+    pub environment: Environment,
+
+    /// Whether the token was rejected in the environment it was routed to
+    /// first, so its hint should be updated to
+    /// [environment](#structfield.environment).
+    pub switched_environment: bool,
+
+    /// The response from APNs.
+    pub response: Response,
+}
+
+/// Sends notifications to production or development, depending on the
+/// environment the device token was registered in.
+///
+/// Created with
+/// [ClientBuilder::build_router](../builder/struct.ClientBuilder.html#method.build_router),
+/// it holds a client for each environment with the same credentials and
+/// settings. Tokens tagged with
+/// [Production](../endpoint/enum.Endpoint.html#variant.Production) or
+/// [Development](../endpoint/enum.Endpoint.html#variant.Development) are
+/// sent to that environment, every other token to the default environment.
+#[derive(Debug, Clone)]
+pub struct RouterClient {
+    production: Client,
+    development: Client,
+    default_environment: Environment,
+    retry_other_environment: bool,
+}
+
+impl RouterClient {
+    /// A router sending to the given clients, which should use the same
+    /// credentials. Untagged tokens go to production, and rejected tokens are
+    /// not retried.
+    pub fn new(production: Client, development: Client) -> RouterClient {
+        RouterClient {
+            production,
+            development,
+            default_environment: Environment::Production,
+            retry_other_environment: false,
+        }
+    }
+
+    /// Sends tokens without an environment hint to `environment`.
+    pub fn with_default_environment(mut self, environment: Environment) -> RouterClient {
+        self.default_environment = environment;
+        self
+    }
+
+    /// Whether to send the notification once more to the other environment
+    /// if APNs answers `BadDeviceToken`, which is what a token registered in
+    /// the other environment gets.
+    pub fn with_retry_other_environment(mut self, retry: bool) -> RouterClient {
+        self.retry_other_environment = retry;
+        self
+    }
+
+    /// The client of the given environment.
+    pub fn client(&self, environment: Environment) -> &Client {
+        match environment {
+            Environment::Production => &self.production,
+            Environment::Development => &self.development,
+        }
+    }
+
+    /// Replace the credentials of both clients, like [Client::reload].
+    pub fn reload(&self, authentication: Authentication<'_>) -> Result<(), Error> {
+        self.production.reload(authentication)?;
+        self.development.reload(authentication)
+    }
+
+    /// The environment a notification to the request's device token is sent
+    /// to first.
+    pub fn route<T>(&self, req: &Request<T>) -> Environment {
+        match req.device_token.environment() {
+            Some(Endpoint::Production) => Environment::Production,
+            Some(Endpoint::Development) => Environment::Development,
+            _ => self.default_environment,
+        }
+    }
+
+    /// Send a notification payload to the environment of its device token,
+    /// like [Client::send].
+    ///
+    /// With
+    /// [with_retry_other_environment](#method.with_retry_other_environment) a
+    /// notification rejected with `BadDeviceToken` is sent to the other
+    /// environment with the same `apns-id`, and its result is returned.
+    pub async fn send<T>(&self, req: Request<T>) -> Result<Routed, Error>
+    where
+        T: Serialize,
+    {
+        let environment = self.route(&req);
+        let device_token = req.device_token.clone();
+        let client = self.client(environment);
+        let request = client.serialize_request(req)?;
+
+        match client.send_request(&request).await {
+            Err(ref e) if self.retry_other_environment && e.reason() == Some(Reason::BadDeviceToken) => {
+                let environment = environment.other();
+
+                #[cfg(feature = "tracing")]
+                {
+                    tracing::debug!(
+                        ?environment,
+                        "RouterClient::send bad device token, trying the other environment"
+                    );
+                }
+
+                let client = self.client(environment);
+                let request = SerializedRequest {
+                    uri: client.device_uri(&device_token)?,
+                    ..request
+                };
+
+                Ok(Routed {
+                    environment,
+                    switched_environment: true,
+                    response: client.send_request(&request).await?,
+                })
+            }
+            result => Ok(Routed {
+                environment,
+                switched_environment: false,
+                response: result?,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{client::ClientBuilder, request::DeviceToken};
+
+    const DEVICE_TOKEN: &str = "a863a50a904a4bb79380aae1e6c80b4dad25fcf8552848599d979b020aece5ae";
+
+    #[test]
+    fn test_route() {
+        let router = ClientBuilder::new().build_router().unwrap();
+        let untagged: DeviceToken = DEVICE_TOKEN.parse().unwrap();
+
+        let request = |device_token: DeviceToken| Request::<()> {
+            device_token,
+            ..Default::default()
+        };
+
+        assert_eq!(Environment::Production, router.route(&request(untagged.clone())));
+        assert_eq!(
+            Environment::Development,
+            router.route(&request(untagged.clone().with_environment(Endpoint::Development)))
+        );
+
+        let router = router.with_default_environment(Environment::Development);
+
+        assert_eq!(Environment::Development, router.route(&request(untagged.clone())));
+        assert_eq!(
+            Environment::Production,
+            router.route(&request(untagged.with_environment(Endpoint::Production)))
+        );
+    }
+
+    #[test]
+    fn test_other_environment() {
+        assert_eq!(Environment::Development, Environment::Production.other());
+        assert_eq!(Endpoint::Development, Environment::Production.other().into());
+    }
+}
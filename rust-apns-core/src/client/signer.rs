@@ -1,24 +1,62 @@
 use crate::error::Error;
+use arc_swap::ArcSwap;
+use async_trait::async_trait;
 use base64::{engine::general_purpose, Engine as _};
+use std::fmt;
 use std::io::Read;
-use std::sync::Arc;
-use std::{
-    sync::RwLock,
-    time::{Duration, SystemTime, UNIX_EPOCH},
-};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-#[cfg(feature = "openssl")]
+#[cfg(all(feature = "openssl", not(feature = "ring")))]
 use openssl::{
     ec::EcKey,
+    ecdsa::EcdsaSig,
     hash::MessageDigest,
     pkey::{PKey, Private},
     sign::Signer as SslSigner,
 };
-#[cfg(all(not(feature = "openssl"), feature = "ring"))]
+#[cfg(feature = "ring")]
 use ring::{rand, signature};
 use thiserror::Error;
 
-#[derive(Debug, Clone)]
+/// APNs rejects provider tokens renewed more often than this with
+/// `TooManyProviderTokenUpdates`, so a signature doesn't expire earlier,
+/// whatever its time to live. Only a signature APNs rejects as expired is
+/// replaced sooner.
+pub const MIN_RENEWAL_INTERVAL: Duration = Duration::from_secs(60 * 20);
+
+/// How long before a signature expires the background refresh renews it, so
+/// requests don't find it expired. Never earlier than
+/// [MIN_RENEWAL_INTERVAL] after it was issued.
+const REFRESH_MARGIN: Duration = Duration::from_secs(60 * 5);
+
+/// How long the background refresh waits after its first failure to renew
+/// the signature, doubled after each consecutive failure.
+const MIN_REFRESH_BACKOFF: Duration = Duration::from_secs(1);
+
+/// The longest the background refresh waits between two failed renewals.
+const MAX_REFRESH_BACKOFF: Duration = Duration::from_secs(60 * 5);
+
+/// The length of an ES256 signature in a JWT: `r` and `s`, 32 bytes each.
+const ES256_SIGNATURE_LENGTH: usize = 64;
+
+/// Signs provider tokens for token-based authentication.
+///
+/// Implement this to keep the private key out of the process, e.g. in a
+/// KMS or an HSM. The signer only calls it to issue a new provider token,
+/// about once per [signature
+/// TTL](../builder/struct.ClientBuilder.html#structfield.signature_ttl), so
+/// a remote call is fine.
+#[async_trait]
+pub trait SigningKey: fmt::Debug + Send + Sync {
+    /// Sign the JWT signing input, the base64url encoded header and claims
+    /// joined by a dot, with ECDSA using P-256 and SHA-256. Returns the 64
+    /// bytes of `r` followed by `s`, not a DER encoded signature.
+    async fn sign(&self, signing_input: &[u8]) -> Result<Vec<u8>, Error>;
+}
+
+#[derive(Debug)]
 struct Signature {
     key: String,
     issued_at: i64,
@@ -26,13 +64,46 @@ struct Signature {
 
 /// For signing requests when using token-based authentication. Re-uses the same
 /// signature for a certain amount of time.
+///
+/// The signature is issued by a [SigningKey], by default one holding the
+/// private key in memory. It is swapped atomically, so taking it out never
+/// blocks. A background task renews it a few minutes before it expires. The
+/// task is spawned by the first request needing a signature, so a signer
+/// created outside of a Tokio runtime, e.g. in a synchronous `main`, gets one
+/// too once it is used inside a runtime. Used outside of one, the signature
+/// is renewed by the first request finding it expired.
+/// Only one renewal runs at a time, the other requests keep using the
+/// current signature meanwhile.
+///
+/// The key can be replaced with [reload](#method.reload) while the signer is
+/// in use, e.g. when rotating keys.
 #[derive(Debug, Clone)]
 pub struct Signer {
-    signature: Arc<RwLock<Signature>>,
+    inner: Arc<SignerInner>,
+}
+
+#[derive(Debug)]
+struct SignerInner {
+    state: ArcSwap<State>,
+    renewal: tokio::sync::Mutex<()>,
+    expire_after_s: Duration,
+    refreshing: AtomicBool,
+}
+
+/// The key and the signature issued with it, swapped together so a reload
+/// never pairs a new key with a signature of the old one.
+#[derive(Debug)]
+struct State {
+    key: Arc<Key>,
+    signature: Option<Arc<Signature>>,
+}
+
+/// The key signing the provider tokens, and whose it is.
+#[derive(Debug)]
+struct Key {
     key_id: String,
     team_id: String,
-    secret: Arc<Secret>,
-    expire_after_s: Duration,
+    signing_key: Arc<dyn SigningKey>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -52,120 +123,334 @@ struct JwtPayload<'a> {
     iat: i64,
 }
 
+/// A [SigningKey] holding a PKCS8 private key in memory, signing with
+/// OpenSSL.
+#[cfg(all(feature = "openssl", not(feature = "ring")))]
 #[derive(Debug)]
-enum Secret {
-    #[cfg(feature = "openssl")]
-    OpenSSL(PKey<Private>),
-    #[cfg(all(not(feature = "openssl"), feature = "ring"))]
-    Ring {
-        signing_key: signature::EcdsaKeyPair,
-        rng: rand::SystemRandom,
-    },
+pub struct OpenSslKey(PKey<Private>);
+
+#[cfg(all(feature = "openssl", not(feature = "ring")))]
+impl OpenSslKey {
+    /// Reads a PKCS8 PEM private key, e.g. the `.p8` file from the Apple
+    /// developer account.
+    pub fn from_pem(pem_key: &[u8]) -> Result<OpenSslKey, Error> {
+        let ec_key = EcKey::private_key_from_pem(pem_key).map_err(SignerError::from)?;
+        let secret = PKey::from_ec_key(ec_key).map_err(SignerError::from)?;
+        Ok(OpenSslKey(secret))
+    }
+
+    fn sign_to_vec(&self, signing_input: &[u8]) -> Result<Vec<u8>, SignerError> {
+        let mut signer = SslSigner::new(MessageDigest::sha256(), &self.0)?;
+        signer.update(signing_input)?;
+
+        // OpenSSL produces a DER encoded signature, but ES256 in a JWT is the
+        // fixed size concatenation of `r` and `s`.
+        let signature = EcdsaSig::from_der(&signer.sign_to_vec()?)?;
+        let mut signature_payload = signature.r().to_vec_padded(32)?;
+        signature_payload.extend(signature.s().to_vec_padded(32)?);
+        Ok(signature_payload)
+    }
 }
 
-impl Secret {
-    #[cfg(feature = "openssl")]
-    fn new_openssl(pem_key: &[u8]) -> Result<Self, Error> {
-        let ec_key = EcKey::private_key_from_pem(pem_key)?;
-        let secret = PKey::from_ec_key(ec_key)?;
-        Ok(Self::OpenSSL(secret))
+#[cfg(all(feature = "openssl", not(feature = "ring")))]
+#[async_trait]
+impl SigningKey for OpenSslKey {
+    async fn sign(&self, signing_input: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(self.sign_to_vec(signing_input)?)
     }
+}
 
-    #[cfg(all(not(feature = "openssl"), feature = "ring"))]
-    fn new_ring(pem_key: &[u8]) -> Result<Self, Error> {
+/// A [SigningKey] holding a PKCS8 private key in memory, signing with ring.
+#[cfg(feature = "ring")]
+#[derive(Debug)]
+pub struct RingKey {
+    signing_key: signature::EcdsaKeyPair,
+    rng: rand::SystemRandom,
+}
+
+#[cfg(feature = "ring")]
+impl RingKey {
+    /// Reads a PKCS8 PEM private key, e.g. the `.p8` file from the Apple
+    /// developer account.
+    pub fn from_pem(pem_key: &[u8]) -> Result<RingKey, Error> {
         let der = pem::parse(pem_key).map_err(SignerError::Pem)?;
         let alg = &signature::ECDSA_P256_SHA256_FIXED_SIGNING;
-        let signing_key = signature::EcdsaKeyPair::from_pkcs8(alg, &der.contents)?;
         let rng = rand::SystemRandom::new();
-        Ok(Self::Ring { signing_key, rng })
+        let signing_key = signature::EcdsaKeyPair::from_pkcs8(alg, &der.contents, &rng)?;
+        Ok(RingKey { signing_key, rng })
     }
+}
 
-    fn from_pem<R>(mut pk_pem: R) -> Result<Secret, Error>
-    where
-        R: Read,
-    {
-        let mut pem_key: Vec<u8> = Vec::new();
-        pk_pem.read_to_end(&mut pem_key)?;
-        #[cfg(feature = "openssl")]
-        {
-            Self::new_openssl(&pem_key)
+#[cfg(feature = "ring")]
+#[async_trait]
+impl SigningKey for RingKey {
+    async fn sign(&self, signing_input: &[u8]) -> Result<Vec<u8>, Error> {
+        let signature_payload = self
+            .signing_key
+            .sign(&self.rng, signing_input)
+            .map_err(SignerError::from)?;
+        Ok(signature_payload.as_ref().to_vec())
+    }
+}
+
+/// A [SigningKey] for tests, returning a fixed signature instead of signing
+/// and remembering what it was asked to sign.
+#[derive(Debug)]
+pub struct MockSigningKey {
+    signature: Vec<u8>,
+    fail: bool,
+    signing_inputs: Mutex<Vec<String>>,
+}
+
+impl MockSigningKey {
+    /// A key returning 64 zero bytes as the signature.
+    pub fn new() -> MockSigningKey {
+        MockSigningKey {
+            signature: vec![0; ES256_SIGNATURE_LENGTH],
+            fail: false,
+            signing_inputs: Mutex::new(Vec::new()),
         }
-        #[cfg(all(not(feature = "openssl"), feature = "ring"))]
-        {
-            Self::new_ring(&pem_key)
+    }
+
+    /// A key failing to sign, like one in an unreachable KMS.
+    pub fn failing() -> MockSigningKey {
+        MockSigningKey {
+            fail: true,
+            ..MockSigningKey::new()
+        }
+    }
+
+    /// The signing inputs of all signatures requested so far.
+    pub fn signing_inputs(&self) -> Vec<String> {
+        self.signing_inputs.lock().unwrap().clone()
+    }
+}
+
+impl Default for MockSigningKey {
+    fn default() -> Self {
+        MockSigningKey::new()
+    }
+}
+
+#[async_trait]
+impl SigningKey for MockSigningKey {
+    async fn sign(&self, signing_input: &[u8]) -> Result<Vec<u8>, Error> {
+        self.signing_inputs
+            .lock()
+            .unwrap()
+            .push(String::from_utf8_lossy(signing_input).into_owned());
+
+        if self.fail {
+            return Err(SignerError::Key("the mock signing key fails".into()).into());
         }
+
+        Ok(self.signature.clone())
+    }
+}
+
+/// Reads a PKCS8 PEM private key into the [SigningKey] of the enabled
+/// backend.
+fn signing_key_from_pem<R>(mut pk_pem: R) -> Result<Arc<dyn SigningKey>, Error>
+where
+    R: Read,
+{
+    let mut pem_key: Vec<u8> = Vec::new();
+    pk_pem.read_to_end(&mut pem_key)?;
+    #[cfg(all(feature = "openssl", not(feature = "ring")))]
+    {
+        Ok(Arc::new(OpenSslKey::from_pem(&pem_key)?))
+    }
+    #[cfg(feature = "ring")]
+    {
+        Ok(Arc::new(RingKey::from_pem(&pem_key)?))
     }
 }
 
 impl Signer {
     /// Creates a signer with a pkcs8 private key, APNs key id and team id.
     /// Can fail if the key is not valid or there is a problem with system OpenSSL.
+    ///
+    /// The `signature_ttl` is at least [MIN_RENEWAL_INTERVAL].
     pub fn new<S, T, R>(pk_pem: R, key_id: S, team_id: T, signature_ttl: Duration) -> Result<Signer, Error>
     where
         S: Into<String>,
         T: Into<String>,
         R: Read,
     {
-        let key_id: String = key_id.into();
-        let team_id: String = team_id.into();
-
-        let secret = Secret::from_pem(pk_pem)?;
-
-        let issued_at = get_time();
-        let signature = RwLock::new(Signature {
-            key: Self::create_signature(&secret, &key_id, &team_id, issued_at)?,
-            issued_at,
-        });
-
-        let signer = Signer {
-            signature: Arc::new(signature),
+        Ok(Signer::with_key(
+            signing_key_from_pem(pk_pem)?,
             key_id,
             team_id,
-            secret: Arc::new(secret),
-            expire_after_s: signature_ttl,
+            signature_ttl,
+        ))
+    }
+
+    /// Creates a signer with a [SigningKey], e.g. one backed by a KMS, its
+    /// APNs key id and team id. Nothing is signed until the first signature
+    /// is needed, and a key failing to sign is only retried by the requests
+    /// needing a signature.
+    ///
+    /// The `signature_ttl` is at least [MIN_RENEWAL_INTERVAL].
+    pub fn with_key<S, T>(signing_key: Arc<dyn SigningKey>, key_id: S, team_id: T, signature_ttl: Duration) -> Signer
+    where
+        S: Into<String>,
+        T: Into<String>,
+    {
+        let key = Key {
+            key_id: key_id.into(),
+            team_id: team_id.into(),
+            signing_key,
         };
 
-        Ok(signer)
+        Signer {
+            inner: Arc::new(SignerInner {
+                state: ArcSwap::from_pointee(State {
+                    key: Arc::new(key),
+                    signature: None,
+                }),
+                renewal: tokio::sync::Mutex::new(()),
+                expire_after_s: signature_ttl.max(MIN_RENEWAL_INTERVAL),
+                refreshing: AtomicBool::new(false),
+            }),
+        }
     }
 
     /// Take a signature out for usage. Automatically renews the signature
     /// if it's older than the expiration time.
-    pub fn with_signature<F, T>(&self, f: F) -> Result<T, Error>
+    pub async fn with_signature<F, T>(&self, f: F) -> Result<T, Error>
     where
         F: FnOnce(&str) -> T,
     {
-        if self.is_expired() {
-            self.renew()?;
-        }
-
-        let signature = self.signature.read().unwrap();
+        let signature = self.signature().await?;
 
         #[cfg(feature = "tracing")]
         {
+            let state = self.inner.state.load();
+
             tracing::trace!(
                 "Signer::with_signature found signature for {}/{} valid for {}s",
-                self.key_id,
-                self.team_id,
-                self.expire_after_s.as_secs(),
+                state.key.key_id,
+                state.key.team_id,
+                self.inner.expire_after_s.as_secs(),
             );
         }
 
         Ok(f(&signature.key))
     }
 
+    /// The current signature and when it was issued, to renew just this one
+    /// if APNs rejects it.
+    pub(crate) async fn signature_issued_at(&self) -> Result<(String, i64), Error> {
+        let signature = self.signature().await?;
+
+        Ok((signature.key.clone(), signature.issued_at))
+    }
+
+    /// Replace the private key, key id and team id, e.g. after rotating the
+    /// key. Requests signed from now on use a signature of the new key,
+    /// requests already sent are not affected. If the key is not valid the
+    /// current one is kept.
+    pub fn reload<S, T, R>(&self, pk_pem: R, key_id: S, team_id: T) -> Result<(), Error>
+    where
+        S: Into<String>,
+        T: Into<String>,
+        R: Read,
+    {
+        self.reload_key(signing_key_from_pem(pk_pem)?, key_id, team_id);
+
+        Ok(())
+    }
+
+    /// Like [reload](#method.reload), replacing the key with a
+    /// [SigningKey].
+    pub fn reload_key<S, T>(&self, signing_key: Arc<dyn SigningKey>, key_id: S, team_id: T)
+    where
+        S: Into<String>,
+        T: Into<String>,
+    {
+        let key = Key {
+            key_id: key_id.into(),
+            team_id: team_id.into(),
+            signing_key,
+        };
+
+        self.inner.state.store(Arc::new(State {
+            key: Arc::new(key),
+            signature: None,
+        }));
+    }
+
+    /// The key id of the current key.
+    pub fn key_id(&self) -> String {
+        self.inner.state.load().key.key_id.clone()
+    }
+
+    /// When the current signature was issued, in seconds since the Unix
+    /// epoch, as in its `iat` claim. `None` until a signature is issued.
+    pub fn issued_at(&self) -> Option<i64> {
+        self.inner.current().map(|signature| signature.issued_at)
+    }
+
+    /// The current signature, issuing one if there is none or it expired.
+    /// While another request renews an expired signature, the expired one is
+    /// used. Starts the background refresh once there is a signature.
+    async fn signature(&self) -> Result<Arc<Signature>, Error> {
+        let signature = self.current_or_renewed().await?;
+
+        self.start_refresh();
+
+        Ok(signature)
+    }
+
+    async fn current_or_renewed(&self) -> Result<Arc<Signature>, Error> {
+        if let Some(signature) = self.inner.current() {
+            if !self.inner.is_expired(&signature) {
+                return Ok(signature);
+            }
+
+            // Another request already renewing keeps using the current one.
+            return match self.inner.renewal.try_lock() {
+                Ok(_renewal) => {
+                    self.inner
+                        .renew_if(|current| current.issued_at == signature.issued_at)
+                        .await
+                }
+                Err(_) => Ok(signature),
+            };
+        }
+
+        let _renewal = self.inner.renewal.lock().await;
+
+        self.inner.renew_if(|_| false).await
+    }
+
+    /// Spawns the background refresh if it isn't running and there is a
+    /// Tokio runtime to run it on.
+    fn start_refresh(&self) {
+        if self.inner.refreshing.load(Ordering::Acquire) {
+            return;
+        }
+
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            if !self.inner.refreshing.swap(true, Ordering::AcqRel) {
+                runtime.spawn(refresh(Arc::downgrade(&self.inner)));
+            }
+        }
+    }
+
     #[inline]
     fn encode<T: AsRef<[u8]>>(input: T) -> String {
-        general_purpose::STANDARD.encode(input)
+        general_purpose::URL_SAFE_NO_PAD.encode(input)
     }
 
-    fn create_signature(secret: &Secret, key_id: &str, team_id: &str, issued_at: i64) -> Result<String, Error> {
+    async fn create_signature(key: &Key, issued_at: i64) -> Result<String, Error> {
         let headers = JwtHeader {
             alg: JwtAlg::ES256,
-            kid: key_id,
+            kid: &key.key_id,
         };
 
         let payload = JwtPayload {
-            iss: team_id,
+            iss: &key.team_id,
             iat: issued_at,
         };
 
@@ -173,56 +458,154 @@ impl Signer {
         let encoded_payload = Signer::encode(serde_json::to_string(&payload)?);
         let signing_input = format!("{}.{}", encoded_header, encoded_payload);
 
-        let signature_payload = secret.sign(&signing_input)?;
+        let signature_payload = key.signing_key.sign(signing_input.as_bytes()).await?;
+
+        if signature_payload.len() != ES256_SIGNATURE_LENGTH {
+            return Err(SignerError::InvalidSignatureLength(signature_payload.len()).into());
+        }
 
         Ok(format!("{}.{}", signing_input, Signer::encode(signature_payload)))
     }
 
-    fn renew(&self) -> Result<(), Error> {
+    /// Replaces the signature issued at `issued_at` with a fresh one when
+    /// APNs reports it as expired. If it was replaced already, e.g. by a
+    /// concurrent request rejected with the same signature, nothing is done.
+    pub(crate) async fn renew(&self, issued_at: i64) -> Result<(), Error> {
+        let _renewal = self.inner.renewal.lock().await;
+
+        self.inner.renew_if(|current| current.issued_at == issued_at).await?;
+
+        Ok(())
+    }
+}
+
+impl SignerInner {
+    fn current(&self) -> Option<Arc<Signature>> {
+        self.state.load().signature.clone()
+    }
+
+    /// Issues a new signature if there is none, or if `renew` is true for
+    /// the current one, and returns the signature to use. The caller holds
+    /// the renewal lock.
+    async fn renew_if<F>(&self, renew: F) -> Result<Arc<Signature>, Error>
+    where
+        F: FnOnce(&Signature) -> bool,
+    {
+        let state = self.state.load_full();
+
+        if let Some(ref signature) = state.signature {
+            if !renew(signature) {
+                return Ok(signature.clone());
+            }
+        }
+
         let issued_at = get_time();
 
         #[cfg(feature = "tracing")]
         {
             tracing::trace!(
                 "Signer::renew for k_id {} t_id {} issued {} valid for {}s",
-                self.key_id,
-                self.team_id,
+                state.key.key_id,
+                state.key.team_id,
                 issued_at,
                 self.expire_after_s.as_secs(),
             );
         }
 
-        let mut signature = self.signature.write().unwrap();
-
-        *signature = Signature {
-            key: Self::create_signature(&self.secret, &self.key_id, &self.team_id, issued_at)?,
+        let signature = Arc::new(Signature {
+            key: Signer::create_signature(&state.key, issued_at).await?,
             issued_at,
-        };
+        });
 
-        Ok(())
+        // A key reloaded while signing wins over the signature of the old one.
+        self.state.compare_and_swap(
+            &state,
+            Arc::new(State {
+                key: state.key.clone(),
+                signature: Some(signature.clone()),
+            }),
+        );
+
+        Ok(signature)
     }
 
-    fn is_expired(&self) -> bool {
-        let sig = self.signature.read().unwrap();
-        let expiry = get_time() - sig.issued_at;
+    fn is_expired(&self, signature: &Signature) -> bool {
+        let expiry = get_time() - signature.issued_at;
         expiry >= self.expire_after_s.as_secs() as i64
     }
+
+    /// How long until the background refresh renews the signature, a margin
+    /// before it expires.
+    fn time_to_refresh(&self, signature: &Signature) -> Duration {
+        let refresh_after = self
+            .expire_after_s
+            .saturating_sub(REFRESH_MARGIN)
+            .max(MIN_RENEWAL_INTERVAL);
+
+        let age = (get_time() - signature.issued_at).max(0) as u64;
+        refresh_after.saturating_sub(Duration::from_secs(age))
+    }
+
+    /// Stops the background refresh when there is no signature to renew, e.g.
+    /// after a reload, so a new key isn't used before a request needs it.
+    /// Returns false if it keeps running after all, because a signature was
+    /// issued meanwhile.
+    fn stop_refresh(&self) -> bool {
+        self.refreshing.store(false, Ordering::Release);
+
+        self.current().is_none() || self.refreshing.swap(true, Ordering::AcqRel)
+    }
 }
 
-impl Secret {
-    fn sign(&self, signing_input: &String) -> Result<Vec<u8>, SignerError> {
-        match self {
-            #[cfg(feature = "openssl")]
-            Secret::OpenSSL(key) => {
-                let mut signer = SslSigner::new(MessageDigest::sha256(), key)?;
-                signer.update(signing_input.as_bytes())?;
-                let signature_payload = signer.sign_to_vec()?;
-                Ok(signature_payload)
+/// Renews the signature shortly before it expires, until the signer is
+/// dropped.
+/// Consecutive failures are retried with an exponential backoff.
+async fn refresh(inner: Weak<SignerInner>) {
+    let mut backoff = MIN_REFRESH_BACKOFF;
+
+    loop {
+        let (issued_at, time_to_refresh) = match inner.upgrade() {
+            Some(inner) => match inner.current() {
+                Some(signature) => (signature.issued_at, inner.time_to_refresh(&signature)),
+                None if inner.stop_refresh() => return,
+                None => continue,
+            },
+            None => return,
+        };
+
+        tokio::time::sleep(time_to_refresh).await;
+
+        let renewed = match inner.upgrade() {
+            Some(inner) => {
+                let _renewal = inner.renewal.lock().await;
+
+                match inner.current() {
+                    Some(current) if current.issued_at == issued_at => inner
+                        .renew_if(|current| current.issued_at == issued_at)
+                        .await
+                        .map(|_| ()),
+                    // Renewed or reloaded meanwhile.
+                    _ => Ok(()),
+                }
             }
-            #[cfg(all(not(feature = "openssl"), feature = "ring"))]
-            Secret::Ring { signing_key, rng } => {
-                let signature_payload = signing_key.sign(rng, signing_input.as_bytes())?;
-                Ok(signature_payload.as_ref().to_vec())
+            None => return,
+        };
+
+        match renewed {
+            Ok(()) => backoff = MIN_REFRESH_BACKOFF,
+            Err(_e) => {
+                #[cfg(feature = "tracing")]
+                {
+                    tracing::warn!(
+                        "Signer::refresh failed to renew the signature, retrying in {}s: {}",
+                        backoff.as_secs(),
+                        _e
+                    );
+                }
+
+                // Requests finding the signature expired try again meanwhile.
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_REFRESH_BACKOFF);
             }
         }
     }
@@ -231,15 +614,23 @@ impl Secret {
 /// Failed to sign payload
 #[derive(Debug, Error)]
 pub enum SignerError {
-    #[cfg(feature = "openssl")]
+    #[cfg(all(feature = "openssl", not(feature = "ring")))]
     #[error(transparent)]
     OpenSSL(#[from] openssl::error::ErrorStack),
-    #[cfg(all(not(feature = "openssl"), feature = "ring"))]
+    #[cfg(feature = "ring")]
     #[error(transparent)]
     Pem(#[from] pem::PemError),
-    #[cfg(all(not(feature = "openssl"), feature = "ring"))]
+    #[cfg(feature = "ring")]
     #[error(transparent)]
     Ring(#[from] ring::error::Unspecified),
+    /// A [SigningKey] couldn't sign, e.g. because the KMS holding the key
+    /// couldn't be reached.
+    #[error("{0}")]
+    Key(Box<dyn std::error::Error + Send + Sync>),
+    /// A [SigningKey] returned something else than the 64 bytes of an ES256
+    /// signature.
+    #[error("Invalid ES256 signature of {0} bytes")]
+    InvalidSignatureLength(usize),
 }
 
 fn get_time() -> i64 {
@@ -259,8 +650,8 @@ lCEIvbDqlUhA5FOzcakkG90E8L+hRANCAATKS2ZExEybUvchRDuKBftotMwVEus3
 jDwmlD1Gg0yJt1e38djFwsxsfr5q2hv0Rj9fTEqAPr8H7mGm0wKxZ7iQ
 -----END PRIVATE KEY-----";
 
-    #[test]
-    fn test_signature_caching() {
+    #[tokio::test]
+    async fn test_signature_caching() {
         let signer = Signer::new(
             PRIVATE_KEY.as_bytes(),
             "89AFRD1X22",
@@ -270,16 +661,30 @@ jDwmlD1Gg0yJt1e38djFwsxsfr5q2hv0Rj9fTEqAPr8H7mGm0wKxZ7iQ
         .unwrap();
 
         let mut sig1 = String::new();
-        signer.with_signature(|sig| sig1.push_str(sig)).unwrap();
+        signer.with_signature(|sig| sig1.push_str(sig)).await.unwrap();
 
         let mut sig2 = String::new();
-        signer.with_signature(|sig| sig2.push_str(sig)).unwrap();
+        signer.with_signature(|sig| sig2.push_str(sig)).await.unwrap();
 
         assert_eq!(sig1, sig2);
     }
 
-    #[test]
-    fn test_signature_without_caching() {
+    /// Makes the current signature of the signer look `age` old.
+    fn age_signature(signer: &Signer, age: Duration) {
+        let state = signer.inner.state.load_full();
+        let current = state.signature.as_ref().unwrap();
+
+        signer.inner.state.store(Arc::new(State {
+            key: state.key.clone(),
+            signature: Some(Arc::new(Signature {
+                key: current.key.clone(),
+                issued_at: current.issued_at - age.as_secs() as i64,
+            })),
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_signature_without_caching() {
         let signer = Signer::new(
             PRIVATE_KEY.as_bytes(),
             "89AFRD1X22",
@@ -289,11 +694,222 @@ jDwmlD1Gg0yJt1e38djFwsxsfr5q2hv0Rj9fTEqAPr8H7mGm0wKxZ7iQ
         .unwrap();
 
         let mut sig1 = String::new();
-        signer.with_signature(|sig| sig1.push_str(sig)).unwrap();
+        signer.with_signature(|sig| sig1.push_str(sig)).await.unwrap();
 
         let mut sig2 = String::new();
-        signer.with_signature(|sig| sig2.push_str(sig)).unwrap();
+        signer.with_signature(|sig| sig2.push_str(sig)).await.unwrap();
+
+        // Not renewed before the minimum renewal interval.
+        assert_eq!(sig1, sig2);
+
+        age_signature(&signer, MIN_RENEWAL_INTERVAL);
+        let issued_at = signer.issued_at().unwrap();
+
+        let mut sig3 = String::new();
+        signer.with_signature(|sig| sig3.push_str(sig)).await.unwrap();
+
+        assert_ne!(sig1, sig3);
+        assert!(signer.issued_at().unwrap() >= issued_at + MIN_RENEWAL_INTERVAL.as_secs() as i64);
+    }
+
+    #[tokio::test]
+    async fn test_renew_once_per_rejected_signature() {
+        let signer = Signer::new(
+            PRIVATE_KEY.as_bytes(),
+            "89AFRD1X22",
+            "ASDFQWERTY",
+            Duration::from_secs(100),
+        )
+        .unwrap();
+
+        signer.with_signature(|_| ()).await.unwrap();
+        age_signature(&signer, Duration::from_secs(10));
+        let rejected = signer.issued_at().unwrap();
+
+        signer.renew(rejected).await.unwrap();
+        let renewed = signer.inner.current().unwrap();
+
+        signer.renew(rejected).await.unwrap();
+
+        assert!(renewed.issued_at > rejected);
+        assert!(Arc::ptr_eq(&renewed, &signer.inner.current().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_before_expiry() {
+        let signer = Signer::with_key(
+            Arc::new(MockSigningKey::new()),
+            "89AFRD1X22",
+            "ASDFQWERTY",
+            Duration::from_secs(60 * 60),
+        );
+
+        signer.with_signature(|_| ()).await.unwrap();
+        let signature = signer.inner.current().unwrap();
+        let time_to_refresh = signer.inner.time_to_refresh(&signature);
+
+        assert!(time_to_refresh <= Duration::from_secs(60 * 55));
+        assert!(time_to_refresh >= Duration::from_secs(60 * 55 - 5));
+
+        age_signature(&signer, Duration::from_secs(60 * 60));
+        let signature = signer.inner.current().unwrap();
+
+        assert_eq!(Duration::ZERO, signer.inner.time_to_refresh(&signature));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_not_before_the_minimum_renewal_interval() {
+        let signer = Signer::with_key(
+            Arc::new(MockSigningKey::new()),
+            "89AFRD1X22",
+            "ASDFQWERTY",
+            Duration::from_secs(100),
+        );
 
+        signer.with_signature(|_| ()).await.unwrap();
+        let signature = signer.inner.current().unwrap();
+
+        assert!(signer.inner.time_to_refresh(&signature) >= MIN_RENEWAL_INTERVAL - Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_background_refresh_stops_with_the_signer() {
+        tokio::time::pause();
+
+        let key = Arc::new(MockSigningKey::new());
+        let signer = Signer::with_key(key.clone(), "89AFRD1X22", "ASDFQWERTY", Duration::from_secs(100));
+
+        signer.with_signature(|_| ()).await.unwrap();
+        let time_to_refresh = signer.inner.time_to_refresh(&signer.inner.current().unwrap());
+        assert!(signer.inner.refreshing.load(Ordering::Acquire));
+
+        // Let the refresh start waiting for the signature to age.
+        tokio::task::yield_now().await;
+        drop(signer);
+
+        tokio::time::advance(time_to_refresh + Duration::from_secs(1)).await;
+        tokio::task::yield_now().await;
+
+        assert_eq!(1, key.signing_inputs().len());
+    }
+
+    #[tokio::test]
+    async fn test_reload() {
+        let signer = Signer::new(
+            PRIVATE_KEY.as_bytes(),
+            "89AFRD1X22",
+            "ASDFQWERTY",
+            Duration::from_secs(100),
+        )
+        .unwrap();
+
+        let mut sig1 = String::new();
+        signer.with_signature(|sig| sig1.push_str(sig)).await.unwrap();
+
+        assert!(signer
+            .reload("not a key".as_bytes(), "OTHERKEY01", "OTHERTEAM1")
+            .is_err());
+        assert_eq!("89AFRD1X22", signer.key_id());
+
+        signer
+            .reload(PRIVATE_KEY.as_bytes(), "OTHERKEY01", "OTHERTEAM1")
+            .unwrap();
+
+        let mut sig2 = String::new();
+        signer.with_signature(|sig| sig2.push_str(sig)).await.unwrap();
+
+        assert_eq!("OTHERKEY01", signer.key_id());
         assert_ne!(sig1, sig2);
     }
+
+    #[tokio::test]
+    async fn test_signature_is_an_es256_jwt() {
+        let signer = Signer::new(
+            PRIVATE_KEY.as_bytes(),
+            "89AFRD1X22",
+            "ASDFQWERTY",
+            Duration::from_secs(100),
+        )
+        .unwrap();
+
+        let mut signature = String::new();
+        signer.with_signature(|sig| signature.push_str(sig)).await.unwrap();
+
+        let parts: Vec<Vec<u8>> = signature
+            .split('.')
+            .map(|part| general_purpose::URL_SAFE_NO_PAD.decode(part).unwrap())
+            .collect();
+
+        assert_eq!(3, parts.len());
+
+        let header: serde_json::Value = serde_json::from_slice(&parts[0]).unwrap();
+        assert_eq!(json!({ "alg": "ES256", "kid": "89AFRD1X22" }), header);
+
+        let payload: serde_json::Value = serde_json::from_slice(&parts[1]).unwrap();
+        assert_eq!(json!("ASDFQWERTY"), payload["iss"]);
+
+        assert_eq!(64, parts[2].len());
+    }
+
+    #[tokio::test]
+    async fn test_signing_key() {
+        let key = Arc::new(MockSigningKey::new());
+        let signer = Signer::with_key(key.clone(), "89AFRD1X22", "ASDFQWERTY", Duration::from_secs(100));
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(key.signing_inputs().is_empty());
+        assert!(!signer.inner.refreshing.load(Ordering::Acquire));
+
+        let mut signature = String::new();
+        signer.with_signature(|sig| signature.push_str(sig)).await.unwrap();
+        signer.with_signature(|_| ()).await.unwrap();
+
+        let signing_inputs = key.signing_inputs();
+
+        assert_eq!(1, signing_inputs.len());
+        assert!(signature.starts_with(&format!("{}.", signing_inputs[0])));
+        assert!(signer.inner.refreshing.load(Ordering::Acquire));
+    }
+
+    #[tokio::test]
+    async fn test_failing_signing_key() {
+        let key = Arc::new(MockSigningKey::failing());
+        let signer = Signer::with_key(key.clone(), "89AFRD1X22", "ASDFQWERTY", Duration::from_secs(100));
+
+        assert!(matches!(
+            signer.with_signature(|_| ()).await,
+            Err(Error::SignerError(SignerError::Key(_)))
+        ));
+        assert_eq!(None, signer.issued_at());
+
+        // Not retried in the background.
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(1, key.signing_inputs().len());
+    }
+
+    /// Returns a DER encoded signature instead of `r` and `s`.
+    #[derive(Debug)]
+    struct DerSigningKey;
+
+    #[async_trait]
+    impl SigningKey for DerSigningKey {
+        async fn sign(&self, _signing_input: &[u8]) -> Result<Vec<u8>, Error> {
+            Ok(vec![0x30; 70])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signature_of_the_wrong_length() {
+        let signer = Signer::with_key(
+            Arc::new(DerSigningKey),
+            "89AFRD1X22",
+            "ASDFQWERTY",
+            Duration::from_secs(100),
+        );
+
+        assert!(matches!(
+            signer.with_signature(|_| ()).await,
+            Err(Error::SignerError(SignerError::InvalidSignatureLength(70)))
+        ));
+    }
 }
@@ -0,0 +1,285 @@
+//! Bounding the notifications in flight to the streams APNs allows
+
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+/// The `SETTINGS` frame type of HTTP/2.
+const SETTINGS_FRAME: u8 = 0x4;
+
+/// The `ACK` flag of a `SETTINGS` frame.
+const SETTINGS_ACK: u8 = 0x1;
+
+/// The identifier of `SETTINGS_MAX_CONCURRENT_STREAMS`.
+const SETTINGS_MAX_CONCURRENT_STREAMS: u16 = 0x3;
+
+/// The length of an HTTP/2 frame header.
+const FRAME_HEADER_LENGTH: usize = 9;
+
+/// Limits the requests in flight to the `SETTINGS_MAX_CONCURRENT_STREAMS`
+/// the server advertised, and to the limit of the client.
+///
+/// Requests over the server's limit would wait in the HTTP/2 connection for a
+/// stream, with their timeout already running, or be refused by the server.
+/// Waiting here instead, a request only starts once it gets a stream. Until
+/// the server's settings are known, one request at a time opens the
+/// connection.
+#[derive(Debug)]
+pub(crate) struct StreamLimiter {
+    state: Mutex<LimiterState>,
+    released: Notify,
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    in_flight: usize,
+    client_limit: usize,
+    /// `None` until the server's settings are known.
+    server_limit: Option<usize>,
+}
+
+/// A request in flight, freeing its stream when dropped.
+pub(crate) struct StreamPermit<'a> {
+    limiter: &'a StreamLimiter,
+}
+
+impl StreamLimiter {
+    pub(crate) fn new(client_limit: usize) -> StreamLimiter {
+        StreamLimiter {
+            state: Mutex::new(LimiterState {
+                in_flight: 0,
+                client_limit: client_limit.max(1),
+                server_limit: None,
+            }),
+            released: Notify::new(),
+        }
+    }
+
+    /// Waits for a free stream.
+    pub(crate) async fn acquire(&self) -> StreamPermit<'_> {
+        loop {
+            // Created before checking, so a release in between isn't missed.
+            let released = self.released.notified();
+
+            if let Some(permit) = self.try_acquire() {
+                return permit;
+            }
+
+            released.await;
+        }
+    }
+
+    fn try_acquire(&self) -> Option<StreamPermit<'_>> {
+        let mut state = self.state.lock().unwrap();
+
+        if state.in_flight >= state.limit() {
+            return None;
+        }
+
+        state.in_flight += 1;
+
+        Some(StreamPermit { limiter: self })
+    }
+
+    /// Applies the `SETTINGS_MAX_CONCURRENT_STREAMS` of a `SETTINGS` frame,
+    /// `None` if the frame didn't change it. Until a server sets a limit, it
+    /// has none.
+    pub(crate) fn update_server_limit(&self, max_concurrent_streams: Option<u32>) {
+        let mut state = self.state.lock().unwrap();
+
+        state.server_limit = match max_concurrent_streams {
+            Some(limit) => Some(limit as usize),
+            None => Some(state.server_limit.unwrap_or(usize::MAX)),
+        };
+
+        drop(state);
+        self.released.notify_waiters();
+    }
+
+    /// Lifts the server's limit for connections without HTTP/2 streams.
+    pub(crate) fn remove_server_limit(&self) {
+        self.state.lock().unwrap().server_limit = Some(usize::MAX);
+        self.released.notify_waiters();
+    }
+}
+
+impl LimiterState {
+    fn limit(&self) -> usize {
+        self.client_limit.min(self.server_limit.unwrap_or(1))
+    }
+}
+
+impl Drop for StreamPermit<'_> {
+    fn drop(&mut self) {
+        self.limiter.state.lock().unwrap().in_flight -= 1;
+        self.limiter.released.notify_waiters();
+    }
+}
+
+/// Follows the frames an HTTP/2 server sends on a connection, passing the
+/// `SETTINGS_MAX_CONCURRENT_STREAMS` of its `SETTINGS` frames to the
+/// [StreamLimiter].
+pub(crate) struct SettingsReader {
+    limiter: Arc<StreamLimiter>,
+    header: [u8; FRAME_HEADER_LENGTH],
+    header_length: usize,
+    remaining: usize,
+    /// The payload of the current frame if it's a `SETTINGS` frame.
+    settings: Option<Vec<u8>>,
+}
+
+impl SettingsReader {
+    pub(crate) fn new(limiter: Arc<StreamLimiter>) -> SettingsReader {
+        SettingsReader {
+            limiter,
+            header: [0; FRAME_HEADER_LENGTH],
+            header_length: 0,
+            remaining: 0,
+            settings: None,
+        }
+    }
+
+    /// Reads the next bytes received from the server.
+    pub(crate) fn read(&mut self, mut bytes: &[u8]) {
+        while !bytes.is_empty() {
+            if self.header_length < FRAME_HEADER_LENGTH {
+                let length = bytes.len().min(FRAME_HEADER_LENGTH - self.header_length);
+                self.header[self.header_length..self.header_length + length].copy_from_slice(&bytes[..length]);
+                self.header_length += length;
+                bytes = &bytes[length..];
+
+                if self.header_length == FRAME_HEADER_LENGTH {
+                    self.start_frame();
+                }
+
+                continue;
+            }
+
+            let length = bytes.len().min(self.remaining);
+
+            if let Some(ref mut settings) = self.settings {
+                settings.extend_from_slice(&bytes[..length]);
+            }
+
+            self.remaining -= length;
+            bytes = &bytes[length..];
+
+            if self.remaining == 0 {
+                self.finish_frame();
+            }
+        }
+    }
+
+    fn start_frame(&mut self) {
+        let [l0, l1, l2, frame_type, flags, ..] = self.header;
+
+        self.remaining = u32::from_be_bytes([0, l0, l1, l2]) as usize;
+        self.settings = (frame_type == SETTINGS_FRAME && flags & SETTINGS_ACK == 0).then(Vec::new);
+
+        if self.remaining == 0 {
+            self.finish_frame();
+        }
+    }
+
+    fn finish_frame(&mut self) {
+        if let Some(settings) = self.settings.take() {
+            // The last value of a setting wins.
+            let max_concurrent_streams = settings
+                .chunks_exact(6)
+                .rev()
+                .find(|setting| u16::from_be_bytes([setting[0], setting[1]]) == SETTINGS_MAX_CONCURRENT_STREAMS)
+                .map(|setting| u32::from_be_bytes([setting[2], setting[3], setting[4], setting[5]]));
+
+            self.limiter.update_server_limit(max_concurrent_streams);
+        }
+
+        self.header_length = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(frame_type: u8, flags: u8, payload: &[u8]) -> Vec<u8> {
+        let length = (payload.len() as u32).to_be_bytes();
+        let mut frame = vec![length[1], length[2], length[3], frame_type, flags, 0, 0, 0, 0];
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    fn in_flight(limiter: &StreamLimiter) -> usize {
+        limiter.state.lock().unwrap().in_flight
+    }
+
+    #[test]
+    fn test_one_stream_until_the_settings_are_known() {
+        let limiter = StreamLimiter::new(100);
+
+        let permit = limiter.try_acquire();
+        assert!(permit.is_some());
+        assert!(limiter.try_acquire().is_none());
+
+        limiter.update_server_limit(None);
+        assert!(limiter.try_acquire().is_some());
+    }
+
+    #[test]
+    fn test_limit_to_the_server_and_the_client() {
+        let limiter = StreamLimiter::new(3);
+
+        limiter.update_server_limit(Some(2));
+        let permits: Vec<_> = (0..3).filter_map(|_| limiter.try_acquire()).collect();
+        assert_eq!(2, permits.len());
+
+        drop(permits);
+        assert_eq!(0, in_flight(&limiter));
+
+        limiter.update_server_limit(Some(1000));
+        let permits: Vec<_> = (0..5).filter_map(|_| limiter.try_acquire()).collect();
+        assert_eq!(3, permits.len());
+    }
+
+    #[test]
+    fn test_read_settings() {
+        let limiter = Arc::new(StreamLimiter::new(1000));
+        let mut reader = SettingsReader::new(limiter.clone());
+
+        // SETTINGS_INITIAL_WINDOW_SIZE and SETTINGS_MAX_CONCURRENT_STREAMS.
+        let settings = frame(SETTINGS_FRAME, 0, &[0, 4, 0, 1, 0, 0, 0, 3, 0, 0, 0, 2]);
+        let mut bytes = frame(0x8, 0, &[0, 0, 0, 1]);
+        bytes.extend(frame(SETTINGS_FRAME, SETTINGS_ACK, &[]));
+        bytes.extend(&settings);
+        bytes.extend(frame(0x0, 0, &[0; 20]));
+
+        // Split anywhere, including inside the frame headers.
+        for chunk in bytes.chunks(5) {
+            reader.read(chunk);
+        }
+
+        assert_eq!(Some(2), limiter.state.lock().unwrap().server_limit);
+
+        reader.read(&frame(SETTINGS_FRAME, 0, &[0, 4, 0, 1, 0, 0]));
+        assert_eq!(Some(2), limiter.state.lock().unwrap().server_limit);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_waits_for_a_release() {
+        let limiter = Arc::new(StreamLimiter::new(1));
+        limiter.update_server_limit(None);
+
+        let permit = limiter.acquire().await;
+
+        let waiting = {
+            let limiter = limiter.clone();
+            tokio::spawn(async move {
+                let _permit = limiter.acquire().await;
+            })
+        };
+
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+
+        drop(permit);
+        waiting.await.unwrap();
+    }
+}
@@ -1,5 +1,9 @@
 ///! Error and result module
-use crate::{client::signer::SignerError, response::response::Response};
+use crate::{
+    client::signer::SignerError,
+    request::Violation,
+    response::{Action, Reason, Response},
+};
 use derive_builder::UninitializedFieldError;
 use std::io;
 use thiserror::Error;
@@ -19,28 +23,62 @@ pub enum Error {
     SignerError(#[from] SignerError),
 
     /// APNs couldn't accept the notification. Contains
-    /// [Response](response/struct.Response.html) with additional
-    /// information.
+    /// [Response](response/struct.Response.html) with the status code, the
+    /// `apns-id` and the [Reason](response/enum.Reason.html) for the
+    /// rejection.
     #[error(
-        "Notification was not accepted by APNs (reason: {})",
-        .0.error
-            .as_ref()
-            .map(|e| e.reason.to_string())
-            .unwrap_or_else(|| "Unknown".to_string())
+        "Notification was not accepted by APNs (status: {}, reason: {:?})",
+        .0.code,
+        .0.reason().unwrap_or(Reason::Unknown)
     )]
     ResponseError(Response),
 
+    /// APNs accepted the request but left out a header the client needs,
+    /// e.g. the `apns-channel-id` of a created channel.
+    #[error("Response from APNs is missing the {0} header")]
+    MissingResponseHeader(http::header::HeaderName),
+
+    /// The serialized payload exceeds the size limit of its push type. Both
+    /// values are in bytes.
+    #[error("Payload too large: {size} bytes exceeds the limit of {limit} bytes")]
+    PayloadTooLarge { size: usize, limit: usize },
+
+    /// The request breaks rules of APNs it would be rejected for. Contains
+    /// every [Violation](request/validation/enum.Violation.html) found.
+    #[error(
+        "Invalid request: {}",
+        .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+    )]
+    InvalidRequest(Vec<Violation>),
+
+    /// A request option couldn't be used as a header value.
+    #[error("Invalid header value: {0}")]
+    InvalidHeaderValue(#[from] http::header::InvalidHeaderValue),
+
+    /// The device token couldn't be joined to the endpoint URL.
+    #[error("Invalid URL: {0}")]
+    InvalidUrl(#[from] url::ParseError),
+
     /// Invalid option values given in
     /// [NotificationOptions](request/notification/struct.NotificationOptions.html)
     #[error("Invalid options for APNs payload: {0}")]
     InvalidOptions(String),
 
+    /// The client couldn't be created with the given settings, e.g. a
+    /// certificate or key that couldn't be parsed.
+    #[error("Invalid client configuration: {0}")]
+    ClientConfigError(String),
+
+    /// APNs didn't respond within the configured request timeout.
+    #[error("Request to APNs timed out")]
+    TimeoutError,
+
     /// Error reading the certificate or private key.
     #[error("Error in reading a certificate file: {0}")]
     ReadError(#[from] io::Error),
 
     /// Unexpected private key (only EC keys are supported).
-    #[cfg(all(not(feature = "openssl"), feature = "ring"))]
+    #[cfg(feature = "ring")]
     #[error("Unexpected private key: {0}")]
     UnexpectedKey(#[from] ring::error::KeyRejected),
 
@@ -48,7 +86,46 @@ pub enum Error {
     BuilderMissingField(String),
 }
 
-#[cfg(feature = "openssl")]
+impl Error {
+    /// The reason APNs gave for rejecting the notification, if it was
+    /// rejected.
+    pub fn reason(&self) -> Option<Reason> {
+        match self {
+            Self::ResponseError(response) => response.reason(),
+            _ => None,
+        }
+    }
+
+    /// What to do after this error.
+    ///
+    /// Rejections are classified by their [Reason], falling back to the
+    /// status code if APNs didn't give a known reason. Connection failures
+    /// and timeouts can be retried later. Invalid requests need fixing and
+    /// unusable keys or certificates refreshing.
+    pub fn action(&self) -> Action {
+        match self {
+            Self::ResponseError(response) => match response.reason() {
+                Some(Reason::Unknown) | None => http::StatusCode::from_u16(response.code)
+                    .map(Action::from_status)
+                    .unwrap_or(Action::RetryLater),
+                Some(reason) => reason.action(),
+            },
+            Self::ConnectionError(_) | Self::TimeoutError | Self::MissingResponseHeader(_) => Action::RetryLater,
+            Self::SignerError(_) | Self::ClientConfigError(_) | Self::ReadError(_) => Action::RefreshCredentials,
+            #[cfg(feature = "ring")]
+            Self::UnexpectedKey(_) => Action::RefreshCredentials,
+            Self::SerializeError(_)
+            | Self::PayloadTooLarge { .. }
+            | Self::InvalidRequest(_)
+            | Self::InvalidHeaderValue(_)
+            | Self::InvalidUrl(_)
+            | Self::InvalidOptions(_)
+            | Self::BuilderMissingField(_) => Action::FixRequest,
+        }
+    }
+}
+
+#[cfg(all(feature = "openssl", not(feature = "ring")))]
 impl From<openssl::error::ErrorStack> for Error {
     fn from(e: openssl::error::ErrorStack) -> Self {
         Self::SignerError(SignerError::OpenSSL(e))
@@ -13,35 +13,43 @@
 //!
 //! ## Payload
 //!
-//! Building the notification payload should be done with the [DefaultNotificationBuilder](request/notification/struct.DefaultNotificationBuilder.html) for most use-cases.
-//! There is also the [WebNotificationBuilder](request/notification/struct.WebNotificationBuilder.html) in the case you need to send notifications to safari
-//!
-//! The payload generated by the builder [can hold a custom data
-//! section](request/payload/struct.Payload.html#method.add_custom_data),
-//! defined by a selected root key. Any data using `#[derive(Serialize)]` from
-//! [Serde](https://serde.rs/) works, allowing usage of type-safe structs or
+//! A notification is described by a [Request](request/struct.Request.html),
+//! holding both the APNs headers and the [Payload](request/payload/struct.Payload.html)
+//! keys. For the common cases the [notification](notification/index.html)
+//! module builds requests for alert, data-only and Safari web push
+//! notifications.
+//!
+//! The request [can hold a custom data
+//! section](request/struct.Request.html#structfield.user_info), which is
+//! flattened into the root of the payload. Any data using `#[derive(Serialize)]`
+//! from [Serde](https://serde.rs/) works, allowing usage of type-safe structs or
 //! dynamic hashmaps to generate the custom data.
 //!
 //! ## Client
 //!
-//! The [asynchronous client](client/struct.Client.html), works either with
-//! [certificate](client/struct.Client.html#method.certificate) or
-//! [token](client/struct.Client.html#method.token) authentication.
+//! The [asynchronous client](client/client/struct.Client.html), works either with
+//! [certificate](client/client/struct.Client.html#method.certificate) or
+//! [token](client/client/struct.Client.html#method.token) authentication. The
+//! [ClientBuilder](client/builder/struct.ClientBuilder.html) gives access to
+//! all connection settings.
 //!
 //! ## Example sending a plain notification using token authentication:
 //!
 //! ```no_run
-//! # use a2::{DefaultNotificationBuilder, NotificationBuilder, Client, Endpoint};
+//! # use rust_apns_core::{client::{client::Client, Endpoint}, request::{Alert, Request}};
 //! # use std::fs::File;
 //! # #[tokio::main]
 //! # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-//! let mut builder = DefaultNotificationBuilder::new()
-//!     .set_body("Hi there")
-//!     .set_badge(420)
-//!     .set_category("cat1")
-//!     .set_sound("ping.flac");
+//! let request = Request::<()> {
+//!     device_token: "hex-device-token-from-the-user".parse()?,
+//!     topic: Some("com.example.myapp".into()),
+//!     alert: Some(Alert::from("Hi there")),
+//!     badge: Some(420),
+//!     category: Some("cat1".into()),
+//!     sound: Some("ping.flac".into()),
+//!     ..Default::default()
+//! };
 //!
-//! let payload = builder.build("device-token-from-the-user", Default::default());
 //! let mut file = File::open("/path/to/private_key.p8")?;
 //!
 //! let client = Client::token(
@@ -50,7 +58,7 @@
 //!     "TEAM_ID",
 //!     Endpoint::Production).unwrap();
 //!
-//! let response = client.send(payload).await?;
+//! let response = client.send(request).await?;
 //! println!("Sent: {:?}", response);
 //! # Ok(())
 //! # }
@@ -60,16 +68,14 @@
 //!
 //! ```no_run
 //! #[macro_use] extern crate serde;
-//! # #[cfg(all(feature = "openssl", not(feature = "ring")))]
-//! # {
 //!
-//! use a2::{
-//!     Client, Endpoint, DefaultNotificationBuilder, NotificationBuilder, NotificationOptions,
-//!     Priority,
+//! use rust_apns_core::{
+//!     client::{client::Client, Endpoint, Priority, PushType},
+//!     request::Request,
 //! };
 //! use std::fs::File;
 //!
-//! #[derive(Serialize, Debug)]
+//! #[derive(Serialize, Debug, Default)]
 //! struct CorporateData {
 //!     tracking_code: &'static str,
 //!     is_paying_user: bool,
@@ -82,15 +88,15 @@
 //!         is_paying_user: false,
 //!     };
 //!
-//!     let mut payload = DefaultNotificationBuilder::new()
-//!         .set_content_available()
-//!         .build("device-token-from-the-user",
-//!         NotificationOptions {
-//!             apns_priority: Some(Priority::Normal),
-//!             ..Default::default()
-//!         },
-//!     );
-//!     payload.add_custom_data("apns_gmbh", &tracking_data)?;
+//!     let request = Request {
+//!         device_token: "hex-device-token-from-the-user".parse()?,
+//!         push_type: PushType::Background,
+//!         priority: Priority::ConsiderPower,
+//!         topic: Some("com.example.myapp".into()),
+//!         content_available: true,
+//!         user_info: Some(tracking_data),
+//!         ..Default::default()
+//!     };
 //!
 //!     let mut file = File::open("/path/to/cert_db.p12")?;
 //!
@@ -99,12 +105,11 @@
 //!         "Correct Horse Battery Stable",
 //!         Endpoint::Production)?;
 //!
-//!     let response = client.send(payload).await?;
+//!     let response = client.send(request).await?;
 //!     println!("Sent: {:?}", response);
 //!
 //!     Ok(())
 //! }
-//! # }
 //! ```
 #[cfg(not(any(feature = "openssl", feature = "ring")))]
 compile_error!("either feature \"openssl\" or feature \"ring\" has to be enabled");
@@ -123,4 +128,4 @@ pub mod request;
 pub mod response;
 
 pub use crate::error::Error;
-// pub use crate::response::{ErrorBody, ErrorReason, Response};
+pub use crate::response::{ErrorBody, Reason, Response};
@@ -28,6 +28,54 @@ pub struct AlertNotification {
     pub badge: Option<u32>,
 }
 
+/// Safari and macOS web push notification, sent to the subscribers of a
+/// website with its website push ID as the topic.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Builder)]
+#[builder(setter(into, strip_option), default, build_fn(error = "Error"))]
+pub struct WebNotification {
+    pub title: String,
+    pub body: String,
+    /// The label of the action button. Safari shows `Show` if not set.
+    pub action: Option<String>,
+    /// The values filled into the placeholders of the `urlFormatString` in
+    /// the push package of the website.
+    pub url_args: Vec<String>,
+}
+
+impl WebNotification {
+    /// Build a request to a device token subscribed to the website. The
+    /// website push ID must have the form `web.` followed by a reverse-DNS
+    /// name, e.g. `web.com.example.domain`.
+    pub fn build_request(self, website_push_id: String, device_token: String) -> Result<Request, Error> {
+        if !is_website_push_id(&website_push_id) {
+            return Err(Error::InvalidOptions(format!(
+                "Invalid website push ID: {website_push_id}, expected the form web.com.example.domain"
+            )));
+        }
+
+        Ok(Request {
+            device_token: device_token.parse()?,
+            push_type: crate::client::PushType::Alert,
+            topic: Some(website_push_id),
+            alert: Some(crate::request::Alert {
+                title: Some(self.title),
+                body: Some(self.body),
+                action: self.action,
+                ..Default::default()
+            }),
+            url_args: Some(self.url_args),
+            ..Default::default()
+        })
+    }
+}
+
+fn is_website_push_id(topic: &str) -> bool {
+    match topic.strip_prefix("web.") {
+        Some(name) => !name.is_empty() && name.split('.').all(|label| !label.is_empty()),
+        None => false,
+    }
+}
+
 /// Push notification.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -46,7 +94,7 @@ impl PushNotification {
     ) -> Result<Request<Value>, Error> {
         match self {
             PushNotification::Data(data) => Ok(Request::<Value> {
-                device_token: device_token,
+                device_token: device_token.parse()?,
                 push_type: crate::client::PushType::Background,
                 id: Some(uid),
                 expiration: Some(OffsetDateTime::now_utc() + Duration::days(1)),
@@ -58,7 +106,7 @@ impl PushNotification {
                 ..Default::default()
             }),
             PushNotification::Alert(alert) => Ok(Request::<Value> {
-                device_token: device_token,
+                device_token: device_token.parse()?,
                 push_type: crate::client::PushType::Alert,
                 id: Some(uid),
                 expiration: Some(OffsetDateTime::now_utc() + Duration::days(1)),
@@ -77,3 +125,49 @@ impl PushNotification {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEVICE_TOKEN: &str = "a863a50a904a4bb79380aae1e6c80b4dad25fcf8552848599d979b020aece5ae";
+
+    fn web_notification() -> WebNotification {
+        WebNotificationBuilder::default()
+            .title("Flight A998 Now Boarding")
+            .body("Boarding has begun for Flight A998.")
+            .action("View")
+            .url_args(vec!["boarding".to_string(), "A998".to_string()])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_web_notification_request() {
+        let request = web_notification()
+            .build_request("web.com.example.domain".into(), DEVICE_TOKEN.into())
+            .unwrap();
+
+        let alert = request.alert.unwrap();
+
+        assert_eq!(Some("web.com.example.domain".to_string()), request.topic);
+        assert_eq!(Some("View".to_string()), alert.action);
+        assert_eq!(Some(vec!["boarding".to_string(), "A998".to_string()]), request.url_args);
+    }
+
+    #[test]
+    fn test_website_push_id() {
+        for topic in [
+            "com.example.domain",
+            "web.",
+            "web..example",
+            "web.com.example.",
+            "webcom.example",
+        ] {
+            assert!(matches!(
+                web_notification().build_request(topic.into(), DEVICE_TOKEN.into()),
+                Err(Error::InvalidOptions(_))
+            ));
+        }
+    }
+}
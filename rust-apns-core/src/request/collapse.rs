@@ -1,5 +1,8 @@
 use crate::Error;
 
+/// The maximum size of a collapse-id in bytes.
+pub const COLLAPSE_ID_LIMIT: usize = 64;
+
 #[derive(Debug, Clone)]
 pub struct CollapseId<'a> {
     pub value: &'a str,
@@ -8,7 +11,7 @@ pub struct CollapseId<'a> {
 /// A collapse-id container. Will not allow bigger id's than 64 bytes.
 impl<'a> CollapseId<'a> {
     pub fn new(value: &'a str) -> Result<CollapseId<'a>, Error> {
-        if value.len() > 64 {
+        if value.len() > COLLAPSE_ID_LIMIT {
             Err(Error::InvalidOptions(String::from(
                 "The collapse-id is too big. Maximum 64 bytes.",
             )))
@@ -0,0 +1,239 @@
+//! Device tokens and the environment they were registered in
+
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+use crate::{client::Endpoint, error::Error};
+
+use super::validation::{is_device_token, Violation};
+
+/// The token APNs issued to an app on a device, as lowercase hex.
+///
+/// Parsing accepts upper and lower case hex of any even length, so tokens
+/// longer than the usual 32 bytes keep working. A token only ever holds hex
+/// digits and can't change the path of the request it is sent with.
+///
+/// A token can be tagged with the [Endpoint] it was registered in, e.g.
+/// [Development](../../client/endpoint/enum.Endpoint.html#variant.Development)
+/// for debug and TestFlight builds. It is serialized as a plain hex string,
+/// or as `{ "token": …, "environment": "development" }` if tagged.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DeviceToken {
+    hex: String,
+    environment: Option<Endpoint>,
+}
+
+impl DeviceToken {
+    /// A token from the raw bytes the device registered with.
+    pub fn from_bytes(bytes: &[u8]) -> DeviceToken {
+        DeviceToken {
+            hex: bytes.iter().map(|byte| format!("{byte:02x}")).collect(),
+            environment: None,
+        }
+    }
+
+    /// Tags the token with the environment it was registered in.
+    pub fn with_environment(mut self, environment: Endpoint) -> DeviceToken {
+        self.environment = Some(environment);
+        self
+    }
+
+    /// The environment the token was registered in, if known.
+    pub fn environment(&self) -> Option<&Endpoint> {
+        self.environment.as_ref()
+    }
+
+    /// The token as lowercase hex.
+    pub fn as_str(&self) -> &str {
+        &self.hex
+    }
+
+    /// The raw bytes of the token.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.hex
+            .as_bytes()
+            .chunks(2)
+            .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap())
+            .collect()
+    }
+
+    /// Whether this is the empty default token, which can't be sent to.
+    pub fn is_empty(&self) -> bool {
+        self.hex.is_empty()
+    }
+}
+
+impl FromStr for DeviceToken {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !is_device_token(s) {
+            return Err(Error::InvalidRequest(vec![Violation::MalformedDeviceToken]));
+        }
+
+        Ok(DeviceToken {
+            hex: s.to_ascii_lowercase(),
+            environment: None,
+        })
+    }
+}
+
+impl TryFrom<&str> for DeviceToken {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl TryFrom<String> for DeviceToken {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for DeviceToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.hex)
+    }
+}
+
+impl AsRef<str> for DeviceToken {
+    fn as_ref(&self) -> &str {
+        &self.hex
+    }
+}
+
+impl PartialEq<str> for DeviceToken {
+    fn eq(&self, other: &str) -> bool {
+        self.hex.eq_ignore_ascii_case(other)
+    }
+}
+
+impl PartialEq<&str> for DeviceToken {
+    fn eq(&self, other: &&str) -> bool {
+        self.hex.eq_ignore_ascii_case(other)
+    }
+}
+
+/// The serialized form of a token tagged with its environment.
+#[derive(Deserialize, Serialize)]
+struct TaggedDeviceToken {
+    token: String,
+    #[serde(serialize_with = "serialize_environment")]
+    environment: Endpoint,
+}
+
+/// Writes the environment as `production` or `development`, which parse back
+/// into the same variant, or as the URL of a custom endpoint.
+fn serialize_environment<S>(environment: &Endpoint, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match environment {
+        Endpoint::Production => serializer.serialize_str("production"),
+        Endpoint::Development => serializer.serialize_str("development"),
+        Endpoint::Custom(url) => serializer.serialize_str(url.as_str()),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SerializedDeviceToken {
+    Plain(String),
+    Tagged(TaggedDeviceToken),
+}
+
+impl Serialize for DeviceToken {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self.environment {
+            Some(ref environment) => TaggedDeviceToken {
+                token: self.hex.clone(),
+                environment: environment.clone(),
+            }
+            .serialize(serializer),
+            None => serializer.serialize_str(&self.hex),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for DeviceToken {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let (token, environment) = match SerializedDeviceToken::deserialize(deserializer)? {
+            SerializedDeviceToken::Plain(token) => (token, None),
+            SerializedDeviceToken::Tagged(tagged) => (tagged.token, Some(tagged.environment)),
+        };
+
+        let token: DeviceToken = token.parse().map_err(serde::de::Error::custom)?;
+
+        Ok(DeviceToken { environment, ..token })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const DEVICE_TOKEN: &str = "a863a50a904a4bb79380aae1e6c80b4dad25fcf8552848599d979b020aece5ae";
+
+    #[test]
+    fn test_parse() {
+        let token: DeviceToken = DEVICE_TOKEN.to_uppercase().parse().unwrap();
+
+        assert_eq!(DEVICE_TOKEN, token.as_str());
+        assert_eq!(token, DEVICE_TOKEN);
+        assert_eq!(None, token.environment());
+
+        let long_token: DeviceToken = "ab".repeat(100).parse().unwrap();
+        assert_eq!(100, long_token.to_bytes().len());
+
+        for malformed in ["", "abc", "a_test_id", "../../3/device/abcd", "abcd/"] {
+            assert!(malformed.parse::<DeviceToken>().is_err());
+        }
+    }
+
+    #[test]
+    fn test_bytes() {
+        let token = DeviceToken::from_bytes(&[0x00, 0xab, 0xff]);
+
+        assert_eq!("00abff", token.as_str());
+        assert_eq!(vec![0x00, 0xab, 0xff], token.to_bytes());
+    }
+
+    #[test]
+    fn test_serde() {
+        let token: DeviceToken = DEVICE_TOKEN.parse().unwrap();
+        assert_eq!(json!(DEVICE_TOKEN), serde_json::to_value(&token).unwrap());
+        assert_eq!(
+            token,
+            serde_json::from_value::<DeviceToken>(json!(DEVICE_TOKEN)).unwrap()
+        );
+
+        let tagged = token.with_environment(Endpoint::Development);
+        let tagged_json = json!({
+            "token": DEVICE_TOKEN,
+            "environment": "development",
+        });
+
+        assert_eq!(tagged_json, serde_json::to_value(&tagged).unwrap());
+        assert_eq!(tagged, serde_json::from_value::<DeviceToken>(tagged_json).unwrap());
+
+        let from_hint: DeviceToken = serde_json::from_value(json!({
+            "token": DEVICE_TOKEN,
+            "environment": "prod",
+        }))
+        .unwrap();
+
+        assert_eq!(Some(&Endpoint::Production), from_hint.environment());
+        assert!(serde_json::from_value::<DeviceToken>(json!("a_test_id")).is_err());
+    }
+}
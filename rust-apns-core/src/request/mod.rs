@@ -1,9 +1,15 @@
 //! The request payload module
 
 pub mod collapse;
+pub mod device_token;
 pub mod payload;
+pub mod prepared;
 pub mod priority;
 pub mod request;
+pub mod validation;
 
-pub use payload::{Alert, InterruptionLevel, Sound};
+pub use device_token::DeviceToken;
+pub use payload::{Alert, InterruptionLevel, LiveActivityEvent, Sound};
+pub use prepared::PreparedNotification;
 pub use request::*;
+pub use validation::Violation;
@@ -1,10 +1,17 @@
 use serde::{
-    de::{self, MapAccess, Visitor},
+    de::{self, DeserializeOwned, MapAccess, Visitor},
     ser::{SerializeMap, SerializeStruct},
     Deserialize, Serialize,
 };
+use serde_json::Value;
 use serde_plain::{derive_display_from_serialize, derive_fromstr_from_deserialize};
 use serde_with::{serde_as, skip_serializing_none, BoolFromInt};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::Error;
+
+/// Appended to an alert text that was cut to fit the payload size limit.
+pub const ELLIPSIS: &str = "…";
 
 fn is_false(v: &bool) -> bool {
     !v
@@ -30,6 +37,92 @@ where
     pub user_info: Option<T>,
 }
 
+impl<T> Payload<T>
+where
+    T: Serialize,
+{
+    /// Serializes the payload into at most `limit` bytes, shortening the alert
+    /// body and then the subtitle if needed.
+    ///
+    /// The text is cut at a grapheme cluster boundary and ends with an
+    /// [ELLIPSIS]. Returns the JSON and the number of bytes removed from it,
+    /// or [PayloadTooLarge](../../error/enum.Error.html#variant.PayloadTooLarge)
+    /// if the payload doesn't fit even without body and subtitle.
+    pub fn to_vec_truncated(&mut self, limit: usize) -> Result<(Vec<u8>, usize), Error> {
+        let mut json = serde_json::to_vec(self)?;
+        let size = json.len();
+
+        for field in [TruncatedField::Body, TruncatedField::Subtitle] {
+            if json.len() <= limit {
+                break;
+            }
+
+            let text = match (self.aps.alert.as_mut(), field) {
+                (Some(alert), TruncatedField::Body) => alert.body.as_mut(),
+                (Some(alert), TruncatedField::Subtitle) => alert.subtitle.as_mut(),
+                (None, _) => None,
+            };
+
+            if let Some(text) = text {
+                truncate_text(text, json.len() - limit);
+                json = serde_json::to_vec(self)?;
+            }
+        }
+
+        if json.len() > limit {
+            return Err(Error::PayloadTooLarge { size, limit });
+        }
+
+        let removed = size - json.len();
+
+        Ok((json, removed))
+    }
+
+    /// The content state of a Live Activity as a typed value.
+    pub fn content_state<C>(&self) -> Result<Option<C>, Error>
+    where
+        C: DeserializeOwned,
+    {
+        match self.aps.content_state {
+            Some(ref content_state) => Ok(Some(C::deserialize(content_state)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum TruncatedField {
+    Body,
+    Subtitle,
+}
+
+/// Shortens `text` so that its JSON encoding is at least `excess` bytes
+/// shorter, ending it with an [ELLIPSIS]. Clears the text if it can't be
+/// shortened enough.
+fn truncate_text(text: &mut String, excess: usize) {
+    let needed = excess + ELLIPSIS.len();
+    let mut removed = 0;
+
+    for (index, grapheme) in text.grapheme_indices(true).rev() {
+        removed += escaped_len(grapheme);
+
+        if removed >= needed {
+            text.truncate(index);
+            text.push_str(ELLIPSIS);
+            return;
+        }
+    }
+
+    text.clear();
+}
+
+/// The length of `text` inside a JSON string.
+fn escaped_len(text: &str) -> usize {
+    serde_json::to_string(text)
+        .map(|json| json.len() - 2)
+        .unwrap_or(text.len())
+}
+
 /// Apple-defined keys.
 #[serde_as]
 #[skip_serializing_none]
@@ -99,6 +192,43 @@ pub struct Aps {
     /// in the notification summary. See
     /// [`relevanceScore`](https://developer.apple.com/documentation/usernotifications/unnotificationcontent/3821031-relevancescore).
     pub relevance_score: Option<f64>,
+
+    /// The flag that tells the system to reload the widgets or controls of
+    /// your app. Required for the `widgets` and `controls` push types.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub content_changed: bool,
+
+    /// The values filled into the placeholders of the `urlFormatString` in the
+    /// push package of a website, for Safari and macOS web push
+    /// notifications.
+    pub url_args: Option<Vec<String>>,
+
+    /// The UNIX timestamp, in seconds, of a Live Activity update. The system
+    /// ignores updates older than the last one it received.
+    pub timestamp: Option<i64>,
+
+    /// Whether to start, update or end a Live Activity.
+    pub event: Option<LiveActivityEvent>,
+
+    /// The dynamic content of a Live Activity. Must match the
+    /// `ContentState` of its `ActivityAttributes`.
+    pub content_state: Option<Value>,
+
+    /// The UNIX timestamp, in seconds, at which a Live Activity becomes
+    /// outdated.
+    pub stale_date: Option<i64>,
+
+    /// The UNIX timestamp, in seconds, at which the system removes an ended
+    /// Live Activity from the Lock Screen.
+    pub dismissal_date: Option<i64>,
+
+    /// The name of the `ActivityAttributes` type of a Live Activity started
+    /// with a push notification.
+    pub attributes_type: Option<String>,
+
+    /// The static attributes of a Live Activity started with a push
+    /// notification.
+    pub attributes: Option<Value>,
 }
 
 /// Alert options.
@@ -121,6 +251,10 @@ pub struct Alert {
     /// file are displayed instead of your app’s normal launch image.
     pub launch_image: Option<String>,
 
+    /// The label of the action button, for Safari and macOS web push
+    /// notifications.
+    pub action: Option<String>,
+
     /// The key for a localized `title` string. Specify this key instead of
     /// the title key to retrieve the title from your app’s
     /// `Localizable.strings` files. The value must contain the name of a
@@ -232,6 +366,7 @@ impl<'de> Deserialize<'de> for Alert {
                         "loc-key" => alert.loc_key = map.next_value()?,
                         "loc-args" => alert.loc_args = map.next_value()?,
                         "launch-image" => alert.launch_image = map.next_value()?,
+                        "action" => alert.action = map.next_value()?,
                         field => {
                             return Err(de::Error::unknown_field(
                                 field,
@@ -246,6 +381,7 @@ impl<'de> Deserialize<'de> for Alert {
                                     "loc-key",
                                     "loc-args",
                                     "launch-image",
+                                    "action",
                                 ],
                             ));
                         }
@@ -274,6 +410,7 @@ impl Serialize for Alert {
             && self.loc_key.is_none()
             && self.loc_args.is_none()
             && self.launch_image.is_none()
+            && self.action.is_none()
         {
             return serializer.serialize_str(self.body.as_deref().unwrap_or_default());
         }
@@ -315,6 +452,11 @@ impl Serialize for Alert {
             len += 1;
         }
 
+        // action
+        if self.action.is_some() {
+            len += 1;
+        }
+
         let mut alert = serializer.serialize_map(Some(len))?;
 
         // title
@@ -352,6 +494,11 @@ impl Serialize for Alert {
             alert.serialize_entry("launch-image", launch_image)?;
         }
 
+        // action
+        if let Some(action) = &self.action {
+            alert.serialize_entry("action", action)?;
+        }
+
         alert.end()
     }
 }
@@ -465,10 +612,7 @@ impl<'de> Deserialize<'de> for Sound {
                             match_volume = true;
                         }
                         field => {
-                            return Err(de::Error::unknown_field(
-                                field,
-                                &["critical", "name", "volume"],
-                            ));
+                            return Err(de::Error::unknown_field(field, &["critical", "name", "volume"]));
                         }
                     }
                 }
@@ -533,6 +677,23 @@ pub enum InterruptionLevel {
 derive_fromstr_from_deserialize!(InterruptionLevel);
 derive_display_from_serialize!(InterruptionLevel);
 
+/// What a Live Activity push notification does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LiveActivityEvent {
+    /// Start a new Live Activity with the given attributes.
+    Start,
+
+    /// Update the content state of a running Live Activity.
+    Update,
+
+    /// End a Live Activity.
+    End,
+}
+
+derive_fromstr_from_deserialize!(LiveActivityEvent);
+derive_display_from_serialize!(LiveActivityEvent);
+
 #[cfg(test)]
 mod test {
     use std::str::FromStr;
@@ -580,6 +741,7 @@ mod test {
                     target_content_id: Some("my-target-id".into()),
                     interruption_level: Some(InterruptionLevel::Active),
                     relevance_score: Some(0.5),
+                    ..Default::default()
                 },
                 user_info: Some(())
             }
@@ -601,10 +763,7 @@ mod test {
                     alert: Some("Hello World!".into()),
                     ..Default::default()
                 },
-                user_info: Some(TestUserInfo {
-                    foo: true,
-                    bar: -10
-                }),
+                user_info: Some(TestUserInfo { foo: true, bar: -10 }),
             }
         );
     }
@@ -624,6 +783,7 @@ mod test {
                     target_content_id: Some("my-target-id".into()),
                     interruption_level: Some(InterruptionLevel::Active),
                     relevance_score: Some(0.5),
+                    ..Default::default()
                 },
                 user_info: Some(()),
             })
@@ -649,10 +809,7 @@ mod test {
                     alert: Some("Hello World!".into()),
                     ..Default::default()
                 },
-                user_info: Some(TestUserInfo {
-                    foo: true,
-                    bar: -10
-                }),
+                user_info: Some(TestUserInfo { foo: true, bar: -10 }),
             })
             .unwrap(),
             json!({
@@ -734,6 +891,7 @@ mod test {
                 subtitle_loc_args: Some(vec!["Bar".into(), "Baz".into()]),
                 loc_key: Some("BODY_FORMAT".into()),
                 loc_args: Some(vec!["Apple".into(), "Pie".into()]),
+                ..Default::default()
             }
         );
     }
@@ -776,6 +934,7 @@ mod test {
                 subtitle_loc_args: Some(vec!["Bar".into(), "Baz".into()]),
                 loc_key: Some("BODY_FORMAT".into()),
                 loc_args: Some(vec!["Apple".into(), "Pie".into()]),
+                ..Default::default()
             })
             .unwrap(),
             json!({
@@ -790,6 +949,53 @@ mod test {
         );
     }
 
+    #[test]
+    fn content_changed_payload() {
+        let payload = Payload::<()> {
+            aps: Aps {
+                content_changed: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let payload_json = json!({ "aps": { "content-changed": true } });
+
+        assert_eq!(payload_json, serde_json::to_value(&payload).unwrap());
+        assert_eq!(payload.aps, Payload::<()>::deserialize(&payload_json).unwrap().aps);
+    }
+
+    #[test]
+    fn web_push_payload() {
+        let payload = Payload::<()> {
+            aps: Aps {
+                alert: Some(Alert {
+                    title: Some("Flight A998 Now Boarding".into()),
+                    body: Some("Boarding has begun for Flight A998.".into()),
+                    action: Some("View".into()),
+                    ..Default::default()
+                }),
+                url_args: Some(vec!["boarding".into(), "A998".into()]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let payload_json = json!({
+            "aps": {
+                "alert": {
+                    "title": "Flight A998 Now Boarding",
+                    "body": "Boarding has begun for Flight A998.",
+                    "action": "View",
+                },
+                "url-args": ["boarding", "A998"],
+            },
+        });
+
+        assert_eq!(payload_json, serde_json::to_value(&payload).unwrap());
+        assert_eq!(payload.aps, Payload::<()>::deserialize(&payload_json).unwrap().aps);
+    }
+
     #[test]
     fn sound_de() {
         assert_eq!(
@@ -920,10 +1126,7 @@ mod test {
 
     #[test]
     fn interruption_level_ser() {
-        assert_eq!(
-            serde_json::to_string(&InterruptionLevel::Active).unwrap(),
-            "\"active\""
-        );
+        assert_eq!(serde_json::to_string(&InterruptionLevel::Active).unwrap(), "\"active\"");
         assert_eq!(
             serde_json::to_string(&InterruptionLevel::Critical).unwrap(),
             "\"critical\""
@@ -959,14 +1162,133 @@ mod test {
         assert!(InterruptionLevel::from_str("invalid").is_err());
     }
 
+    #[test]
+    fn payload_truncated_body() {
+        let mut payload = Payload::<()> {
+            aps: Aps {
+                alert: Some("👍🏽".repeat(1000).into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let size = serde_json::to_vec(&payload).unwrap().len();
+        let (json, removed) = payload.to_vec_truncated(4096).unwrap();
+
+        assert!(json.len() <= 4096);
+        assert_eq!(size - json.len(), removed);
+
+        let body = payload.aps.alert.unwrap().body.unwrap();
+        assert!(body.ends_with(&format!("👍🏽{}", ELLIPSIS)));
+    }
+
+    #[test]
+    fn payload_truncated_subtitle() {
+        let mut payload = Payload::<()> {
+            aps: Aps {
+                alert: Some(Alert {
+                    subtitle: Some("\"quoted\"".repeat(500)),
+                    body: Some("body".into()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let (json, removed) = payload.to_vec_truncated(4096).unwrap();
+
+        assert!(json.len() <= 4096);
+        assert!(removed > 0);
+
+        let alert = payload.aps.alert.unwrap();
+        assert_eq!(Some(String::new()), alert.body);
+        assert!(alert.subtitle.unwrap().ends_with(ELLIPSIS));
+    }
+
+    #[test]
+    fn payload_not_truncated() {
+        let mut payload = Payload::<()> {
+            aps: Aps {
+                alert: Some("Hello World!".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let (json, removed) = payload.to_vec_truncated(4096).unwrap();
+
+        assert_eq!(serde_json::to_vec(&payload).unwrap(), json);
+        assert_eq!(0, removed);
+    }
+
+    #[test]
+    fn payload_too_large_to_truncate() {
+        let mut payload = Payload::<()> {
+            aps: Aps {
+                alert: Some(Alert {
+                    title: Some("a".repeat(5000)),
+                    body: Some("body".into()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            payload.to_vec_truncated(4096),
+            Err(Error::PayloadTooLarge { limit: 4096, .. })
+        ));
+    }
+
+    #[test]
+    fn live_activity_payload() {
+        #[derive(Debug, PartialEq, Deserialize, Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Score {
+            home_score: u32,
+            away_score: u32,
+        }
+
+        let payload_json = json!({
+            "aps": {
+                "timestamp": 1685952000,
+                "event": "update",
+                "content-state": { "homeScore": 2, "awayScore": 1 },
+                "stale-date": 1685955600,
+                "dismissal-date": 1685959200
+            }
+        });
+
+        let payload = Payload::<()>::deserialize(&payload_json).unwrap();
+
+        assert_eq!(Some(LiveActivityEvent::Update), payload.aps.event);
+        assert_eq!(Some(1685952000), payload.aps.timestamp);
+        assert_eq!(Some(1685955600), payload.aps.stale_date);
+        assert_eq!(Some(1685959200), payload.aps.dismissal_date);
+        assert_eq!(
+            Some(Score {
+                home_score: 2,
+                away_score: 1
+            }),
+            payload.content_state().unwrap()
+        );
+        assert_eq!(payload_json, serde_json::to_value(&payload).unwrap());
+    }
+
+    #[test]
+    fn live_activity_event_to_str() {
+        assert_eq!(LiveActivityEvent::Start.to_string(), "start");
+        assert_eq!(LiveActivityEvent::Update.to_string(), "update");
+        assert_eq!(LiveActivityEvent::End.to_string(), "end");
+    }
+
     #[test]
     fn interruption_level_to_str() {
         assert_eq!(InterruptionLevel::Active.to_string(), "active");
         assert_eq!(InterruptionLevel::Critical.to_string(), "critical");
         assert_eq!(InterruptionLevel::Passive.to_string(), "passive");
-        assert_eq!(
-            InterruptionLevel::TimeSensitive.to_string(),
-            "time-sensitive"
-        );
+        assert_eq!(InterruptionLevel::TimeSensitive.to_string(), "time-sensitive");
     }
 }
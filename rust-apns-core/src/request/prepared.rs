@@ -0,0 +1,36 @@
+//! Notifications serialized once for many device tokens
+
+use http::HeaderMap;
+use hyper::body::Bytes;
+
+/// A notification with headers and a JSON body built once from a
+/// [Request](../request/struct.Request.html) template, ready to be sent to
+/// any number of device tokens without serializing the payload again.
+///
+/// Created with
+/// [Client::prepare](../../client/client/struct.Client.html#method.prepare).
+/// Cloning only copies the headers, the body is shared.
+#[derive(Clone, Debug)]
+pub struct PreparedNotification {
+    pub(crate) headers: HeaderMap,
+    pub(crate) body: Bytes,
+    pub(crate) truncated_bytes: usize,
+}
+
+impl PreparedNotification {
+    /// The `apns-*` and content headers sent with every notification.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// The serialized JSON payload.
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// The number of bytes removed from the payload by shortening the alert
+    /// to fit the size limit.
+    pub fn truncated_bytes(&self) -> usize {
+        self.truncated_bytes
+    }
+}
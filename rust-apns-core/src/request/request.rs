@@ -1,21 +1,24 @@
 use http::{header, HeaderMap, HeaderValue};
 use serde::Serialize;
+use serde_json::Value;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
 use crate::client::header::*;
 use crate::client::{Priority, PushType};
 use crate::request::payload::{Aps, Payload, Sound};
-use crate::response::Result;
-use crate::response::result::Error;
+use crate::Error;
 
-use super::{Alert, InterruptionLevel};
+use super::{
+    validation::{notification_violations, Violation},
+    Alert, DeviceToken, InterruptionLevel, LiveActivityEvent,
+};
 
 /// Apple Push Notification service request options.
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Request<T = ()> {
-    /// The hex-encoded device token.
-    pub device_token: String,
+    /// The device token, parsed from hex or raw bytes.
+    pub device_token: DeviceToken,
 
     /// (Required for watchOS 6 and later; recommended for macOS, iOS, tvOS, and
     /// iPadOS) The value of this header must accurately reflect the contents of
@@ -139,17 +142,105 @@ pub struct Request<T = ()> {
     /// [`relevanceScore`](https://developer.apple.com/documentation/usernotifications/unnotificationcontent/3821031-relevancescore).
     pub relevance_score: Option<f64>,
 
+    /// The flag that tells the system to reload the widgets or controls of
+    /// your app. Required for the `widgets` and `controls` push types.
+    pub content_changed: bool,
+
+    /// The values filled into the placeholders of the `urlFormatString` in
+    /// the push package of a website, for Safari and macOS web push
+    /// notifications.
+    pub url_args: Option<Vec<String>>,
+
+    /// (Required for Live Activities) The time of the Live Activity update.
+    /// The system ignores updates older than the last one it received.
+    pub timestamp: Option<OffsetDateTime>,
+
+    /// (Required for Live Activities) Whether to start, update or end a Live
+    /// Activity.
+    pub event: Option<LiveActivityEvent>,
+
+    /// (Required to start or update a Live Activity) The dynamic content of
+    /// the Live Activity. Must match the `ContentState` of its
+    /// `ActivityAttributes`. See [`Request::with_content_state`].
+    pub content_state: Option<Value>,
+
+    /// The time at which the Live Activity becomes outdated.
+    pub stale_date: Option<OffsetDateTime>,
+
+    /// The time at which the system removes an ended Live Activity from the
+    /// Lock Screen.
+    pub dismissal_date: Option<OffsetDateTime>,
+
+    /// (Required to start a Live Activity) The name of the
+    /// `ActivityAttributes` type of the Live Activity.
+    pub attributes_type: Option<String>,
+
+    /// (Required to start a Live Activity) The static attributes of the Live
+    /// Activity. See [`Request::with_attributes`].
+    pub attributes: Option<Value>,
+
     /// Additional data to send.
     pub user_info: Option<T>,
 }
 
+impl<T> Request<T> {
+    /// Checks the request for mistakes APNs would reject it for, such as a
+    /// malformed device token, a collapse-id over 64 bytes or a background
+    /// notification with priority 10. Fails with
+    /// [InvalidRequest](../../error/enum.Error.html#variant.InvalidRequest)
+    /// listing every [Violation] found.
+    ///
+    /// The client validates every request before sending it.
+    pub fn validate(&self) -> Result<(), Error> {
+        let mut violations = Vec::new();
+
+        if self.device_token.is_empty() {
+            violations.push(Violation::MalformedDeviceToken);
+        }
+
+        violations.extend(notification_violations(self));
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InvalidRequest(violations))
+        }
+    }
+
+    /// Sets the dynamic content of a Live Activity from a typed value.
+    pub fn with_content_state<C>(mut self, content_state: &C) -> Result<Self, Error>
+    where
+        C: Serialize,
+    {
+        self.content_state = Some(serde_json::to_value(content_state)?);
+        Ok(self)
+    }
+
+    /// Sets the `ActivityAttributes` type name and the static attributes of a
+    /// Live Activity from a typed value.
+    pub fn with_attributes<A>(mut self, attributes_type: impl Into<String>, attributes: &A) -> Result<Self, Error>
+    where
+        A: Serialize,
+    {
+        self.attributes_type = Some(attributes_type.into());
+        self.attributes = Some(serde_json::to_value(attributes)?);
+        Ok(self)
+    }
+}
+
 impl<T> TryFrom<Request<T>> for (HeaderMap<HeaderValue>, Payload<T>)
 where
     T: Serialize,
 {
     type Error = Error;
 
-    fn try_from(this: Request<T>) -> Result<Self> {
+    fn try_from(this: Request<T>) -> Result<Self, Error> {
+        let violations = notification_violations(&this);
+
+        if !violations.is_empty() {
+            return Err(Error::InvalidRequest(violations));
+        }
+
         let mut headers = HeaderMap::new();
 
         headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
@@ -180,20 +271,8 @@ where
             let _ = headers.insert(APNS_COLLAPSE_ID.clone(), collapse_id);
         }
 
-        let is_critical = this
-            .interruption_level
-            .as_ref()
-            .map(|il| *il == InterruptionLevel::Critical)
-            .unwrap_or_default();
-
-        let is_critical_sound = this.sound.as_ref().map(|sound| sound.critical).unwrap_or_default();
-
-        if is_critical != is_critical_sound {
-            return Err(Error::CriticalSound);
-        }
-
         let sound = this.sound.map(|mut sound| {
-            sound.critical = is_critical || is_critical_sound;
+            sound.critical = this.interruption_level == Some(InterruptionLevel::Critical);
             sound
         });
 
@@ -209,6 +288,15 @@ where
                 target_content_id: this.target_content_id,
                 interruption_level: this.interruption_level,
                 relevance_score: this.relevance_score,
+                content_changed: this.content_changed,
+                url_args: this.url_args,
+                timestamp: this.timestamp.map(OffsetDateTime::unix_timestamp),
+                event: this.event,
+                content_state: this.content_state,
+                stale_date: this.stale_date.map(OffsetDateTime::unix_timestamp),
+                dismissal_date: this.dismissal_date.map(OffsetDateTime::unix_timestamp),
+                attributes_type: this.attributes_type,
+                attributes: this.attributes,
             },
             user_info: this.user_info,
         };
@@ -0,0 +1,244 @@
+//! Checking requests for mistakes APNs would reject them for
+
+use crate::client::{Priority, PushType};
+
+use super::{collapse::COLLAPSE_ID_LIMIT, InterruptionLevel, LiveActivityEvent, Request};
+
+/// A rule of APNs the request breaks.
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum Violation {
+    #[error("the device token must be an even number of hexadecimal digits")]
+    MalformedDeviceToken,
+
+    #[error("the collapse-id is {0} bytes, the maximum is 64")]
+    CollapseIdTooLong(usize),
+
+    #[error("background notifications must not use priority 10")]
+    BackgroundPriority,
+
+    #[error("background notifications must not have an alert, badge or sound")]
+    BackgroundInteraction,
+
+    #[error("push to talk notifications must use priority 10")]
+    PushToTalkPriority,
+
+    #[error("{0} notifications require content-changed")]
+    MissingContentChanged(PushType),
+
+    #[error("{0} notifications must not have an alert, badge or sound")]
+    ReloadInteraction(PushType),
+
+    #[error("the relevance score {0} is not between 0 and 1")]
+    RelevanceScore(f64),
+
+    #[error("the sound volume {0} is not between 0 and 1")]
+    SoundVolume(f64),
+
+    #[error("the interruption level does not match the critical flag of the sound")]
+    CriticalSound,
+
+    #[error("Live Activity notifications require an event")]
+    MissingEvent,
+
+    #[error("Live Activity notifications require a timestamp")]
+    MissingTimestamp,
+
+    #[error("Live Activity {0} events require a content state")]
+    MissingContentState(LiveActivityEvent),
+
+    #[error("Live Activity start events require attributes and their type")]
+    MissingAttributes,
+}
+
+/// Whether `device_token` looks like a token APNs issued.
+pub(crate) fn is_device_token(device_token: &str) -> bool {
+    !device_token.is_empty()
+        && device_token
+            .as_bytes()
+            .chunks(2)
+            .all(|pair| pair.len() == 2 && pair.iter().all(u8::is_ascii_hexdigit))
+}
+
+/// The violations of the request, not counting the device token.
+pub(crate) fn notification_violations<T>(request: &Request<T>) -> Vec<Violation> {
+    let mut violations = Vec::new();
+
+    if let Some(ref collapse_id) = request.collapse_id {
+        if collapse_id.len() > COLLAPSE_ID_LIMIT {
+            violations.push(Violation::CollapseIdTooLong(collapse_id.len()));
+        }
+    }
+
+    match request.push_type {
+        PushType::Background => {
+            if request.priority == Priority::Immediate {
+                violations.push(Violation::BackgroundPriority);
+            }
+
+            if request.alert.is_some() || request.badge.is_some() || request.sound.is_some() {
+                violations.push(Violation::BackgroundInteraction);
+            }
+        }
+        PushType::Pushtotalk if request.priority != Priority::Immediate => {
+            violations.push(Violation::PushToTalkPriority);
+        }
+        PushType::Widgets | PushType::Controls => {
+            if !request.content_changed {
+                violations.push(Violation::MissingContentChanged(request.push_type));
+            }
+
+            if request.alert.is_some() || request.badge.is_some() || request.sound.is_some() {
+                violations.push(Violation::ReloadInteraction(request.push_type));
+            }
+        }
+        PushType::Liveactivity => {
+            if request.timestamp.is_none() {
+                violations.push(Violation::MissingTimestamp);
+            }
+
+            match request.event {
+                None => violations.push(Violation::MissingEvent),
+                Some(event) => {
+                    if event != LiveActivityEvent::End && request.content_state.is_none() {
+                        violations.push(Violation::MissingContentState(event));
+                    }
+
+                    if event == LiveActivityEvent::Start
+                        && (request.attributes_type.is_none() || request.attributes.is_none())
+                    {
+                        violations.push(Violation::MissingAttributes);
+                    }
+                }
+            }
+        }
+        _ => (),
+    }
+
+    if let Some(relevance_score) = request.relevance_score {
+        if !(0.0..=1.0).contains(&relevance_score) {
+            violations.push(Violation::RelevanceScore(relevance_score));
+        }
+    }
+
+    if let Some(ref sound) = request.sound {
+        if !(0.0..=1.0).contains(&sound.volume) {
+            violations.push(Violation::SoundVolume(sound.volume));
+        }
+    }
+
+    let is_critical = request.interruption_level == Some(InterruptionLevel::Critical);
+    let is_critical_sound = request.sound.as_ref().map(|sound| sound.critical).unwrap_or_default();
+
+    if is_critical != is_critical_sound {
+        violations.push(Violation::CriticalSound);
+    }
+
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::Sound;
+
+    const DEVICE_TOKEN: &str = "a863a50a904a4bb79380aae1e6c80b4dad25fcf8552848599d979b020aece5ae";
+
+    #[test]
+    fn test_device_tokens() {
+        assert!(is_device_token(DEVICE_TOKEN));
+        assert!(is_device_token(&DEVICE_TOKEN.to_uppercase()));
+
+        assert!(!is_device_token(""));
+        assert!(!is_device_token("a_test_id"));
+        assert!(!is_device_token(&DEVICE_TOKEN[1..]));
+    }
+
+    #[test]
+    fn test_valid_request() {
+        let request = Request::<()> {
+            device_token: DEVICE_TOKEN.parse().unwrap(),
+            alert: Some("Hello World!".into()),
+            relevance_score: Some(1.0),
+            ..Default::default()
+        };
+
+        assert!(notification_violations(&request).is_empty());
+    }
+
+    #[test]
+    fn test_all_violations() {
+        let request = Request::<()> {
+            push_type: PushType::Background,
+            collapse_id: Some("a".repeat(65)),
+            content_available: true,
+            badge: Some(1),
+            sound: Some(Sound {
+                critical: true,
+                name: "default".into(),
+                volume: 2.0,
+            }),
+            relevance_score: Some(-0.5),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            vec![
+                Violation::CollapseIdTooLong(65),
+                Violation::BackgroundPriority,
+                Violation::BackgroundInteraction,
+                Violation::RelevanceScore(-0.5),
+                Violation::SoundVolume(2.0),
+                Violation::CriticalSound,
+            ],
+            notification_violations(&request)
+        );
+    }
+
+    #[test]
+    fn test_push_type_rules() {
+        let push_to_talk = Request::<()> {
+            push_type: PushType::Pushtotalk,
+            priority: Priority::ConsiderPower,
+            ..Default::default()
+        };
+
+        let widgets = Request::<()> {
+            push_type: PushType::Widgets,
+            ..Default::default()
+        };
+
+        let controls = Request::<()> {
+            push_type: PushType::Controls,
+            content_changed: true,
+            alert: Some("Hello World!".into()),
+            ..Default::default()
+        };
+
+        let live_activity = Request::<()> {
+            push_type: PushType::Liveactivity,
+            event: Some(LiveActivityEvent::Start),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            vec![Violation::PushToTalkPriority],
+            notification_violations(&push_to_talk)
+        );
+        assert_eq!(
+            vec![Violation::MissingContentChanged(PushType::Widgets)],
+            notification_violations(&widgets)
+        );
+        assert_eq!(
+            vec![Violation::ReloadInteraction(PushType::Controls)],
+            notification_violations(&controls)
+        );
+        assert_eq!(
+            vec![
+                Violation::MissingTimestamp,
+                Violation::MissingContentState(LiveActivityEvent::Start),
+                Violation::MissingAttributes,
+            ],
+            notification_violations(&live_activity)
+        );
+    }
+}
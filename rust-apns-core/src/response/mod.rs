@@ -1,6 +1,5 @@
 pub mod reason;
 pub mod response;
-pub mod result;
 
-pub use result::*;
-pub use reason::*;
\ No newline at end of file
+pub use reason::{Action, Reason};
+pub use response::{ErrorBody, Response};
@@ -1,13 +1,8 @@
 use http::StatusCode;
 use serde::{Deserialize, Serialize};
-use serde_with::{serde_as, skip_serializing_none, TimestampMilliSeconds};
-use std::time::SystemTime;
-
-/// APNS error response reason JSON body.
-#[serde_as]
-#[skip_serializing_none]
-#[derive(thiserror::Error, Debug, PartialEq, Eq, Deserialize, Serialize)]
-#[serde(tag = "reason")]
+
+/// The reason APNs gave for rejecting a notification.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum Reason {
     #[error("The collapse identifier exceeds the maximum allowed size.")]
     BadCollapseId,
@@ -39,6 +34,18 @@ pub enum Reason {
     #[error("The apns-push-type value is invalid.")]
     InvalidPushType,
 
+    #[error("The apns-channel-id value is invalid.")]
+    BadChannelId,
+
+    #[error("The apns-channel-id header of the request isn’t specified and is required.")]
+    MissingChannelId,
+
+    #[error("The channel isn’t registered for the bundle ID.")]
+    ChannelNotRegistered,
+
+    #[error("The message-storage-policy value is invalid.")]
+    InvalidPolicy,
+
     #[error("The device token isn’t specified in the request :path. Verify that the :path header contains the device token.")]
     MissingDeviceToken,
 
@@ -52,22 +59,10 @@ pub enum Reason {
     TopicDisallowed,
 
     #[error("The certificate is invalid.")]
-    BadCertificate {
-        /// The time, in milliseconds since Epoch, at which APNs confirmed the token
-        /// was no longer valid for the topic. This key is included only when the
-        /// error in the `:status` field is 410.
-        #[serde_as(as = "Option<TimestampMilliSeconds>")]
-        timestamp: Option<SystemTime>,
-    },
+    BadCertificate,
 
     #[error("The client certificate is for the wrong environment.")]
-    BadCertificateEnvironment {
-        /// The time, in milliseconds since Epoch, at which APNs confirmed the token
-        /// was no longer valid for the topic. This key is included only when the
-        /// error in the `:status` field is 410.
-        #[serde_as(as = "Option<TimestampMilliSeconds>")]
-        timestamp: Option<SystemTime>,
-    },
+    BadCertificateEnvironment,
 
     #[error("The provider token is stale and a new token should be generated.")]
     ExpiredProviderToken,
@@ -116,6 +111,77 @@ pub enum Reason {
     Unknown,
 }
 
+/// What to do after APNs rejected a notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// The device token is no longer valid for the topic. Remove it and stop
+    /// sending notifications to it.
+    DropToken,
+
+    /// The device token is invalid or belongs to the other environment, e.g.
+    /// a development token sent to production. Check which environment the
+    /// token was registered for before dropping it, or let a
+    /// [RouterClient](../../client/router/struct.RouterClient.html#method.with_retry_other_environment)
+    /// try the other environment.
+    CheckEnvironment,
+
+    /// The request is invalid. Sending it again fails the same way until it
+    /// is fixed.
+    FixRequest,
+
+    /// The certificate or provider token was rejected. Check or replace the
+    /// credentials before sending again.
+    RefreshCredentials,
+
+    /// This notification failed for a transient reason and can be sent again
+    /// later.
+    RetryLater,
+
+    /// APNs is overloaded or unavailable. Slow down sending all notifications.
+    BackOff,
+}
+
+impl Action {
+    /// The action for an HTTP status code, used when APNs didn't give a known
+    /// reason.
+    pub fn from_status(status: StatusCode) -> Self {
+        match status {
+            StatusCode::FORBIDDEN => Action::RefreshCredentials,
+            StatusCode::GONE => Action::DropToken,
+            StatusCode::TOO_MANY_REQUESTS => Action::RetryLater,
+            StatusCode::SERVICE_UNAVAILABLE => Action::BackOff,
+            status if status.is_client_error() => Action::FixRequest,
+            _ => Action::RetryLater,
+        }
+    }
+}
+
+impl Reason {
+    /// What to do after APNs rejected a notification for this reason.
+    ///
+    /// Reasons are classified by their [StatusCode], except for:
+    ///
+    /// * `BadDeviceToken`, which APNs also returns for a valid token sent to
+    ///   the wrong environment, so the environment should be checked first.
+    /// * `DeviceTokenNotForTopic`, which means the token can't be used and
+    ///   should be dropped.
+    /// * `IdleTimeout`, which can be retried.
+    /// * `TooManyProviderTokenUpdates`, which asks to slow down instead of
+    ///   renewing credentials again.
+    /// * `Unknown`, which can be retried. With the status code at hand prefer
+    ///   [Error::action](../../error/enum.Error.html#method.action).
+    pub fn action(&self) -> Action {
+        match self {
+            Reason::BadDeviceToken => Action::CheckEnvironment,
+            Reason::DeviceTokenNotForTopic => Action::DropToken,
+            Reason::IdleTimeout => Action::RetryLater,
+            Reason::TooManyProviderTokenUpdates => Action::BackOff,
+            Reason::Unknown => Action::RetryLater,
+            reason => Action::from_status((*reason).into()),
+        }
+    }
+}
+
 impl From<Reason> for StatusCode {
     fn from(this: Reason) -> Self {
         match this {
@@ -129,12 +195,16 @@ impl From<Reason> for StatusCode {
             Reason::DuplicateHeaders => StatusCode::BAD_REQUEST,
             Reason::IdleTimeout => StatusCode::BAD_REQUEST,
             Reason::InvalidPushType => StatusCode::BAD_REQUEST,
+            Reason::BadChannelId => StatusCode::BAD_REQUEST,
+            Reason::MissingChannelId => StatusCode::BAD_REQUEST,
+            Reason::ChannelNotRegistered => StatusCode::BAD_REQUEST,
+            Reason::InvalidPolicy => StatusCode::BAD_REQUEST,
             Reason::MissingDeviceToken => StatusCode::BAD_REQUEST,
             Reason::MissingTopic => StatusCode::BAD_REQUEST,
             Reason::PayloadEmpty => StatusCode::BAD_REQUEST,
             Reason::TopicDisallowed => StatusCode::BAD_REQUEST,
-            Reason::BadCertificate { .. } => StatusCode::FORBIDDEN,
-            Reason::BadCertificateEnvironment { .. } => StatusCode::FORBIDDEN,
+            Reason::BadCertificate => StatusCode::FORBIDDEN,
+            Reason::BadCertificateEnvironment => StatusCode::FORBIDDEN,
             Reason::ExpiredProviderToken => StatusCode::FORBIDDEN,
             Reason::Forbidden => StatusCode::FORBIDDEN,
             Reason::InvalidProviderToken => StatusCode::FORBIDDEN,
@@ -153,3 +223,63 @@ impl From<Reason> for StatusCode {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reason_actions() {
+        let actions = vec![
+            (Reason::BadCollapseId, Action::FixRequest),
+            (Reason::BadDeviceToken, Action::CheckEnvironment),
+            (Reason::BadExpirationDate, Action::FixRequest),
+            (Reason::BadMessageId, Action::FixRequest),
+            (Reason::BadPriority, Action::FixRequest),
+            (Reason::BadTopic, Action::FixRequest),
+            (Reason::DeviceTokenNotForTopic, Action::DropToken),
+            (Reason::DuplicateHeaders, Action::FixRequest),
+            (Reason::IdleTimeout, Action::RetryLater),
+            (Reason::InvalidPushType, Action::FixRequest),
+            (Reason::BadChannelId, Action::FixRequest),
+            (Reason::MissingChannelId, Action::FixRequest),
+            (Reason::ChannelNotRegistered, Action::FixRequest),
+            (Reason::InvalidPolicy, Action::FixRequest),
+            (Reason::MissingDeviceToken, Action::FixRequest),
+            (Reason::MissingTopic, Action::FixRequest),
+            (Reason::PayloadEmpty, Action::FixRequest),
+            (Reason::TopicDisallowed, Action::FixRequest),
+            (Reason::BadCertificate, Action::RefreshCredentials),
+            (Reason::BadCertificateEnvironment, Action::RefreshCredentials),
+            (Reason::ExpiredProviderToken, Action::RefreshCredentials),
+            (Reason::Forbidden, Action::RefreshCredentials),
+            (Reason::InvalidProviderToken, Action::RefreshCredentials),
+            (Reason::MissingProviderToken, Action::RefreshCredentials),
+            (Reason::BadPath, Action::FixRequest),
+            (Reason::MethodNotAllowed, Action::FixRequest),
+            (Reason::ExpiredToken, Action::DropToken),
+            (Reason::Unregistered, Action::DropToken),
+            (Reason::PayloadTooLarge, Action::FixRequest),
+            (Reason::TooManyProviderTokenUpdates, Action::BackOff),
+            (Reason::TooManyRequests, Action::RetryLater),
+            (Reason::InternalServerError, Action::RetryLater),
+            (Reason::ServiceUnavailable, Action::BackOff),
+            (Reason::Shutdown, Action::BackOff),
+            (Reason::Unknown, Action::RetryLater),
+        ];
+
+        for (reason, action) in actions {
+            assert_eq!(action, reason.action(), "{:?}", reason);
+        }
+    }
+
+    #[test]
+    fn test_status_actions() {
+        assert_eq!(Action::FixRequest, Action::from_status(StatusCode::BAD_REQUEST));
+        assert_eq!(Action::RefreshCredentials, Action::from_status(StatusCode::FORBIDDEN));
+        assert_eq!(Action::DropToken, Action::from_status(StatusCode::GONE));
+        assert_eq!(Action::RetryLater, Action::from_status(StatusCode::TOO_MANY_REQUESTS));
+        assert_eq!(Action::RetryLater, Action::from_status(StatusCode::BAD_GATEWAY));
+        assert_eq!(Action::BackOff, Action::from_status(StatusCode::SERVICE_UNAVAILABLE));
+    }
+}
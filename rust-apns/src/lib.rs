@@ -0,0 +1,79 @@
+//! # rust-apns
+//!
+//! An asynchronous client to Apple Push Notification service, re-exporting the
+//! stable parts of [rust-apns-core](https://docs.rs/rust-apns-core).
+//!
+//! ## Features
+//!
+//! - `openssl` (default): TLS and token signing with OpenSSL, required for
+//!   PKCS12 certificates and custom certificate authorities.
+//! - `ring`: token signing with ring, when `openssl` is disabled.
+//! - `tracing`: instrument the client with [tracing](https://docs.rs/tracing).
+//! - `http1`: allow HTTP/1.1 connections to custom `http` endpoints, for HTTP
+//!   mock servers without HTTP/2 support.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rust_apns::{Alert, Authentication, ClientBuilder, Endpoint, Request};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+//! let key_pem = std::fs::read("/path/to/private_key.p8")?;
+//!
+//! let client = ClientBuilder {
+//!     endpoint: Endpoint::Production,
+//!     authentication: Some(Authentication::Token {
+//!         key_id: "KEY_ID",
+//!         key_pem: &key_pem,
+//!         team_id: "TEAM_ID",
+//!     }),
+//!     ..Default::default()
+//! }
+//! .build()?;
+//!
+//! let request = Request::<()> {
+//!     device_token: "hex-device-token-from-the-user".parse()?,
+//!     topic: Some("com.example.myapp".into()),
+//!     alert: Some(Alert::from("Hello World!")),
+//!     ..Default::default()
+//! };
+//!
+//! let apns_id = client.post(request).await?;
+//! println!("Sent: {}", apns_id);
+//! # Ok(())
+//! # }
+//! ```
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+pub use rust_apns_core::{
+    client::{
+        builder::{DEFAULT_MAX_CONCURRENT_STREAMS, DEFAULT_POOL_IDLE_TIMEOUT, DEFAULT_SIGNATURE_TTL},
+        client::{Client, Delivery, USER_AGENT},
+        header::{PAYLOAD_SIZE_LIMIT, VOIP_PAYLOAD_SIZE_LIMIT},
+        signer::{MockSigningKey, Signer, SigningKey, MIN_RENEWAL_INTERVAL},
+        AppConfig, Authentication, CertificateAuthority, CertificateInfo, CertificateTopic, Channel, ChannelClient,
+        ClientBuilder, Credentials, Endpoint, Environment, MessageStoragePolicy, Priority, PushType, Registry,
+        RegistryConfig, RetryPolicy, Routed, RouterClient,
+    },
+    notification::{
+        AlertNotification, AlertNotificationBuilder, DataNotification, PushNotification, WebNotification,
+        WebNotificationBuilder,
+    },
+    request::{
+        collapse::CollapseId,
+        device_token::DeviceToken,
+        payload::{Aps, Payload, ELLIPSIS},
+        Alert, InterruptionLevel, LiveActivityEvent, PreparedNotification, Request, Sound,
+    },
+    response::{Action, ErrorBody, Reason, Response},
+    Error,
+};
+
+#[cfg(all(feature = "openssl", not(feature = "ring")))]
+pub use rust_apns_core::client::signer::OpenSslKey;
+#[cfg(feature = "ring")]
+pub use rust_apns_core::client::signer::RingKey;
+
+/// A result with the client [Error] by default.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
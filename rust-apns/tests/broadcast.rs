@@ -0,0 +1,93 @@
+use futures_util::StreamExt;
+use rust_apns::*;
+use std::collections::HashSet;
+use tokio::test;
+use wiremock::{
+    matchers::{body_string, header, method},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const TOPIC: &str = "com.example.myapp";
+
+fn create_apns_client(mock_server_uri: &str) -> Client {
+    ClientBuilder {
+        endpoint: Endpoint::Custom(format!("{mock_server_uri}/3/device/").parse().unwrap()),
+        ..Default::default()
+    }
+    .build()
+    .unwrap()
+}
+
+fn create_template() -> Request {
+    Request {
+        topic: Some(TOPIC.into()),
+        alert: Some("Hello World!".into()),
+        ..Default::default()
+    }
+}
+
+#[test]
+async fn broadcast_sends_the_same_body_to_every_token() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(header("apns-topic", TOPIC))
+        .and(body_string(r#"{"aps":{"alert":"Hello World!"}}"#))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(20)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_apns_client(&mock_server.uri());
+    let notification = client.prepare(create_template()).unwrap();
+
+    let tokens: Vec<DeviceToken> = (0..20).map(|i| DeviceToken::from_bytes(&[i; 32])).collect();
+    let deliveries: Vec<Delivery> = client.broadcast(&notification, tokens.clone()).collect().await;
+
+    let delivered: HashSet<&str> = deliveries
+        .iter()
+        .filter(|delivery| delivery.result.is_ok())
+        .map(|delivery| delivery.device_token.as_str())
+        .collect();
+
+    let apns_ids: HashSet<_> = deliveries.iter().map(|delivery| delivery.apns_id).collect();
+
+    assert_eq!(
+        tokens.iter().map(DeviceToken::as_str).collect::<HashSet<_>>(),
+        delivered
+    );
+    assert_eq!(20, apns_ids.len());
+}
+
+#[test]
+async fn send_prepared() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_apns_client(&mock_server.uri());
+    let notification = client.prepare(create_template()).unwrap();
+
+    assert!(client
+        .send_prepared(&notification, &"0a1b".parse().unwrap())
+        .await
+        .is_ok());
+    assert!(client
+        .send_prepared(&notification, &"2C3D".parse().unwrap())
+        .await
+        .is_ok());
+
+    let paths: Vec<String> = mock_server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| request.url.path().to_string())
+        .collect();
+
+    assert_eq!(vec!["/3/device/0a1b", "/3/device/2c3d"], paths);
+}
@@ -0,0 +1,164 @@
+use rust_apns::*;
+use serde_json::json;
+use time::OffsetDateTime;
+use tokio::test;
+use wiremock::{
+    matchers::{body_json, header, header_exists, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const BUNDLE_ID: &str = "com.example.myapp";
+const CHANNEL_ID: &str = "dHN0LXNyY2gtY2hubA==";
+const REQUEST_ID: &str = "4d947500-498e-4524-8aa8-7220c4e65d75";
+
+fn create_channel_client(mock_server_uri: &str) -> ChannelClient {
+    ClientBuilder {
+        endpoint: Endpoint::Custom(format!("{mock_server_uri}/3/device/").parse().unwrap()),
+        ..Default::default()
+    }
+    .build()
+    .unwrap()
+    .channels(BUNDLE_ID)
+}
+
+#[test]
+async fn create_channel() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path(format!("/1/apps/{BUNDLE_ID}/channels")))
+        .and(header_exists("apns-request-id"))
+        .and(header("content-type", "application/json"))
+        .and(body_json(
+            json!({ "message-storage-policy": 1, "push-type": "LiveActivity" }),
+        ))
+        .respond_with(
+            ResponseTemplate::new(201)
+                .insert_header("apns-channel-id", CHANNEL_ID)
+                .insert_header("apns-request-id", REQUEST_ID),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let channels = create_channel_client(&mock_server.uri());
+    let channel = channels
+        .create_channel(MessageStoragePolicy::MostRecentMessageStored)
+        .await
+        .unwrap();
+
+    assert_eq!(CHANNEL_ID, channel.channel_id);
+    assert_eq!(
+        MessageStoragePolicy::MostRecentMessageStored,
+        channel.message_storage_policy
+    );
+    assert_eq!(Some(REQUEST_ID.to_string()), channel.request_id);
+}
+
+#[test]
+async fn read_list_and_delete_channels() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path(format!("/1/apps/{BUNDLE_ID}/channels")))
+        .and(header("apns-channel-id", CHANNEL_ID))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "message-storage-policy": 0, "push-type": "LiveActivity" })),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path(format!("/1/apps/{BUNDLE_ID}/all-channels")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "channels": [CHANNEL_ID] })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("DELETE"))
+        .and(path(format!("/1/apps/{BUNDLE_ID}/channels")))
+        .and(header("apns-channel-id", CHANNEL_ID))
+        .respond_with(ResponseTemplate::new(204).insert_header("apns-request-id", REQUEST_ID))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let channels = create_channel_client(&mock_server.uri());
+
+    let channel = channels.read_channel(CHANNEL_ID).await.unwrap();
+    assert_eq!(MessageStoragePolicy::NoMessageStored, channel.message_storage_policy);
+
+    assert_eq!(vec![CHANNEL_ID.to_string()], channels.list_channels().await.unwrap());
+
+    let response = channels.delete_channel(CHANNEL_ID).await.unwrap();
+    assert_eq!(204, response.code);
+    assert_eq!(Some(REQUEST_ID.to_string()), response.request_id);
+}
+
+#[test]
+async fn broadcast_push() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path(format!("/4/broadcasts/apps/{BUNDLE_ID}")))
+        .and(header("apns-channel-id", CHANNEL_ID))
+        .and(header("apns-push-type", "liveactivity"))
+        .and(header_exists("apns-request-id"))
+        .and(body_json(json!({
+            "aps": {
+                "timestamp": 1685952000,
+                "event": "update",
+                "content-state": { "homeScore": 1, "awayScore": 0 },
+            },
+        })))
+        .respond_with(ResponseTemplate::new(200).insert_header("apns-request-id", REQUEST_ID))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let channels = create_channel_client(&mock_server.uri());
+
+    let request = Request::<()> {
+        push_type: PushType::Liveactivity,
+        timestamp: Some(OffsetDateTime::from_unix_timestamp(1685952000).unwrap()),
+        event: Some(LiveActivityEvent::Update),
+        content_state: Some(json!({ "homeScore": 1, "awayScore": 0 })),
+        ..Default::default()
+    };
+
+    let response = channels.send(CHANNEL_ID, request).await.unwrap();
+
+    assert_eq!(Some(REQUEST_ID.to_string()), response.request_id);
+}
+
+#[test]
+async fn channel_not_registered() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({ "reason": "ChannelNotRegistered" })))
+        .mount(&mock_server)
+        .await;
+
+    let channels = create_channel_client(&mock_server.uri());
+    let error = channels.read_channel(CHANNEL_ID).await.unwrap_err();
+
+    assert_eq!(Some(Reason::ChannelNotRegistered), error.reason());
+}
+
+#[test]
+async fn broadcast_requires_liveactivity() {
+    let channels = create_channel_client("http://localhost");
+
+    let request = Request::<()> {
+        alert: Some("Hello World!".into()),
+        ..Default::default()
+    };
+
+    assert!(matches!(
+        channels.send(CHANNEL_ID, request).await,
+        Err(Error::InvalidOptions(_))
+    ));
+}
@@ -1,5 +1,6 @@
-use apple_apns::*;
+use rust_apns::*;
 use serde_json::json;
+use std::time::{Duration, SystemTime};
 use tokio::test;
 use wiremock::{
     matchers::{body_json, header, method, path},
@@ -47,7 +48,7 @@ async fn client() {
     let client = create_apns_client(&mock_server.uri());
 
     let request = Request::<()> {
-        device_token: DEVICE_TOKEN.into(),
+        device_token: DEVICE_TOKEN.parse().unwrap(),
         id: Some(APS_ID.parse().unwrap()),
         topic: Some(TOPIC.into()),
         alert: Some(Alert {
@@ -64,3 +65,160 @@ async fn client() {
 
     assert_eq!(APS_ID, aps_id.unwrap().hyphenated().to_string());
 }
+
+#[test]
+async fn post_returns_the_generated_apns_id() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_apns_client(&mock_server.uri());
+
+    let request = Request::<()> {
+        device_token: DEVICE_TOKEN.parse().unwrap(),
+        topic: Some(TOPIC.into()),
+        alert: Some("Hello World!".into()),
+        ..Default::default()
+    };
+
+    let apns_id = client.post(request).await.unwrap();
+    let received = mock_server.received_requests().await.unwrap();
+    let sent_apns_id = received[0].headers.get(&"apns-id".into()).unwrap().last().as_str();
+
+    assert!(!apns_id.is_nil());
+    assert_eq!(apns_id.hyphenated().to_string(), sent_apns_id);
+}
+
+#[test]
+async fn payload_too_large() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_apns_client(&mock_server.uri());
+
+    let request = Request::<()> {
+        device_token: DEVICE_TOKEN.parse().unwrap(),
+        topic: Some(TOPIC.into()),
+        alert: Some("a".repeat(PAYLOAD_SIZE_LIMIT).into()),
+        ..Default::default()
+    };
+
+    let result = client.post(request).await;
+
+    assert!(matches!(
+        result,
+        Err(Error::PayloadTooLarge {
+            limit: PAYLOAD_SIZE_LIMIT,
+            ..
+        })
+    ));
+}
+
+#[test]
+async fn truncated_alert() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path(format!("/3/device/{DEVICE_TOKEN}")))
+        .respond_with(ResponseTemplate::new(200).insert_header("apns-id", APS_ID))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = ClientBuilder {
+        endpoint: Endpoint::Custom(format!("{}/3/device/", mock_server.uri()).parse().unwrap()),
+        user_agent: USER_AGENT,
+        truncate_alerts: true,
+        ..Default::default()
+    }
+    .build()
+    .unwrap();
+
+    let request = Request::<()> {
+        device_token: DEVICE_TOKEN.parse().unwrap(),
+        topic: Some(TOPIC.into()),
+        alert: Some("a".repeat(PAYLOAD_SIZE_LIMIT).into()),
+        ..Default::default()
+    };
+
+    let response = client.send(request).await.unwrap();
+
+    let received = mock_server.received_requests().await.unwrap();
+    assert!(received[0].body.len() <= PAYLOAD_SIZE_LIMIT);
+    assert!(received[0].body.ends_with(format!("{ELLIPSIS}\"}}}}").as_bytes()));
+    assert!(response.truncated_bytes > 0);
+}
+
+#[test]
+async fn unregistered() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(410)
+                .insert_header("apns-id", APS_ID)
+                .set_body_json(json!({
+                    "reason": "Unregistered",
+                    "timestamp": 1508249865488u64,
+                })),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let client = create_apns_client(&mock_server.uri());
+
+    let request = Request::<()> {
+        device_token: DEVICE_TOKEN.parse().unwrap(),
+        topic: Some(TOPIC.into()),
+        alert: Some("Hello World!".into()),
+        ..Default::default()
+    };
+
+    let error = client.send(request).await.unwrap_err();
+
+    assert_eq!(Some(Reason::Unregistered), error.reason());
+    assert_eq!(Action::DropToken, error.action());
+
+    match error {
+        Error::ResponseError(response) => {
+            assert_eq!(410, response.code);
+            assert_eq!(Some(APS_ID), response.apns_id.as_deref());
+            assert_eq!(
+                Some(SystemTime::UNIX_EPOCH + Duration::from_millis(1508249865488)),
+                response.timestamp()
+            );
+        }
+        error => panic!("unexpected error: {error}"),
+    }
+}
+
+#[test]
+async fn rejected_without_reason() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(502).set_body_string("Bad Gateway"))
+        .mount(&mock_server)
+        .await;
+
+    let client = create_apns_client(&mock_server.uri());
+
+    let request = Request::<()> {
+        device_token: DEVICE_TOKEN.parse().unwrap(),
+        alert: Some("Hello World!".into()),
+        ..Default::default()
+    };
+
+    let error = client.send(request).await.unwrap_err();
+
+    assert_eq!(Some(Reason::Unknown), error.reason());
+    assert_eq!(Action::RetryLater, error.action());
+}
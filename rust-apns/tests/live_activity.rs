@@ -0,0 +1,159 @@
+use rust_apns::*;
+use serde::Serialize;
+use serde_json::json;
+use time::OffsetDateTime;
+use tokio::test;
+use wiremock::{
+    matchers::{body_json, header, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const DEVICE_TOKEN: &str = "a863a50a904a4bb79380aae1e6c80b4dad25fcf8552848599d979b020aece5ae";
+const TOPIC: &str = "com.example.myapp.push-type.liveactivity";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Score {
+    home_score: u32,
+    away_score: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Match {
+    home_team: &'static str,
+    away_team: &'static str,
+}
+
+fn create_apns_client(mock_server_uri: &str) -> Client {
+    ClientBuilder {
+        endpoint: Endpoint::Custom(format!("{mock_server_uri}/3/device/").parse().unwrap()),
+        ..Default::default()
+    }
+    .build()
+    .unwrap()
+}
+
+fn create_request(event: LiveActivityEvent) -> Request {
+    Request {
+        device_token: DEVICE_TOKEN.parse().unwrap(),
+        push_type: PushType::Liveactivity,
+        topic: Some(TOPIC.into()),
+        timestamp: Some(OffsetDateTime::from_unix_timestamp(1685952000).unwrap()),
+        event: Some(event),
+        ..Default::default()
+    }
+}
+
+#[test]
+async fn start_live_activity() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path(format!("/3/device/{DEVICE_TOKEN}")))
+        .and(header("apns-push-type", "liveactivity"))
+        .and(header("apns-topic", TOPIC))
+        .and(body_json(json!({
+            "aps": {
+                "timestamp": 1685952000,
+                "event": "start",
+                "content-state": { "homeScore": 0, "awayScore": 0 },
+                "stale-date": 1685959200,
+                "attributes-type": "MatchAttributes",
+                "attributes": { "homeTeam": "Lions", "awayTeam": "Tigers" },
+                "alert": { "title": "Kick-off", "body": "Lions vs. Tigers" },
+            },
+        })))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_apns_client(&mock_server.uri());
+
+    let request = Request {
+        stale_date: Some(OffsetDateTime::from_unix_timestamp(1685959200).unwrap()),
+        alert: Some(Alert {
+            title: Some("Kick-off".into()),
+            body: Some("Lions vs. Tigers".into()),
+            ..Default::default()
+        }),
+        ..create_request(LiveActivityEvent::Start)
+    }
+    .with_content_state(&Score {
+        home_score: 0,
+        away_score: 0,
+    })
+    .unwrap()
+    .with_attributes(
+        "MatchAttributes",
+        &Match {
+            home_team: "Lions",
+            away_team: "Tigers",
+        },
+    )
+    .unwrap();
+
+    assert!(client.send(request).await.is_ok());
+}
+
+#[test]
+async fn end_live_activity() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(header("apns-push-type", "liveactivity"))
+        .and(body_json(json!({
+            "aps": {
+                "timestamp": 1685952000,
+                "event": "end",
+                "dismissal-date": 1685955600,
+            },
+        })))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_apns_client(&mock_server.uri());
+
+    let request = Request {
+        dismissal_date: Some(OffsetDateTime::from_unix_timestamp(1685955600).unwrap()),
+        ..create_request(LiveActivityEvent::End)
+    };
+
+    assert!(client.send(request).await.is_ok());
+}
+
+#[test]
+async fn missing_live_activity_keys() {
+    let client = create_apns_client("http://localhost");
+
+    let without_timestamp = Request {
+        timestamp: None,
+        ..create_request(LiveActivityEvent::End)
+    };
+
+    let without_event = Request {
+        event: None,
+        ..create_request(LiveActivityEvent::End)
+    };
+
+    let without_content_state = create_request(LiveActivityEvent::Update);
+
+    let without_attributes = create_request(LiveActivityEvent::Start)
+        .with_content_state(&Score {
+            home_score: 0,
+            away_score: 0,
+        })
+        .unwrap();
+
+    for request in [
+        without_timestamp,
+        without_event,
+        without_content_state,
+        without_attributes,
+    ] {
+        assert!(matches!(client.send(request).await, Err(Error::InvalidRequest(_))));
+    }
+}
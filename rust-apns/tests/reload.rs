@@ -0,0 +1,97 @@
+use rust_apns::*;
+use std::time::Duration;
+use tokio::test;
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const DEVICE_TOKEN: &str = "a863a50a904a4bb79380aae1e6c80b4dad25fcf8552848599d979b020aece5ae";
+const APS_ID: &str = "4d947500-498e-4524-8aa8-7220c4e65d75";
+const TOPIC: &str = "com.example.myapp";
+
+const IDENTITY_PEM: &[u8] = include_bytes!("../../rust-apns-core/testdata/identity.pem");
+const CERTIFICATE_PEM: &[u8] = include_bytes!("../../rust-apns-core/testdata/certificate.pem");
+const PRIVATE_KEY_PEM: &[u8] = include_bytes!("../../rust-apns-core/testdata/private_key.pem");
+
+fn create_certificate_client(mock_server_uri: &str) -> Client {
+    let endpoint = Endpoint::Custom(format!("{mock_server_uri}/3/device/").parse().unwrap());
+    Client::certificate_pem(IDENTITY_PEM, endpoint).unwrap()
+}
+
+fn create_request() -> Request {
+    Request {
+        device_token: DEVICE_TOKEN.parse().unwrap(),
+        topic: Some(TOPIC.into()),
+        alert: Some(Alert {
+            body: Some("Hello World!".into()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+#[test]
+async fn in_flight_notification_finishes_after_certificate_reload() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path(format!("/3/device/{DEVICE_TOKEN}")))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("apns-id", APS_ID)
+                .set_delay(Duration::from_millis(300)),
+        )
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_certificate_client(&mock_server.uri());
+
+    let in_flight = tokio::spawn({
+        let client = client.clone();
+        async move { client.send(create_request()).await }
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    client
+        .reload(Authentication::CertificateAndKey {
+            cert_pem: CERTIFICATE_PEM,
+            key_pem: PRIVATE_KEY_PEM,
+            key_password: None,
+        })
+        .unwrap();
+
+    assert_eq!(200, in_flight.await.unwrap().unwrap().code);
+    assert_eq!(200, client.send(create_request()).await.unwrap().code);
+}
+
+#[test]
+async fn invalid_certificate_is_not_reloaded() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path(format!("/3/device/{DEVICE_TOKEN}")))
+        .respond_with(ResponseTemplate::new(200).insert_header("apns-id", APS_ID))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_certificate_client(&mock_server.uri());
+
+    let reloaded = client.reload(Authentication::Certificate {
+        client_pem: b"not a certificate",
+    });
+
+    assert!(matches!(reloaded, Err(Error::ClientConfigError(_))));
+    assert!(client
+        .reload(Authentication::Token {
+            key_id: "89AFRD1X22",
+            key_pem: PRIVATE_KEY_PEM,
+            team_id: "ASDFQWERTY",
+        })
+        .is_err());
+
+    assert_eq!(200, client.send(create_request()).await.unwrap().code);
+}
@@ -0,0 +1,158 @@
+use rust_apns::*;
+use serde_json::json;
+use std::time::Duration;
+use tokio::test;
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const DEVICE_TOKEN: &str = "a863a50a904a4bb79380aae1e6c80b4dad25fcf8552848599d979b020aece5ae";
+const TOPIC: &str = "com.example.myapp";
+
+fn create_retrying_client(mock_server_uri: &str) -> Client {
+    ClientBuilder {
+        endpoint: Endpoint::Custom(format!("{mock_server_uri}/3/device/").parse().unwrap()),
+        retry_policy: Some(RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+            deadline: None,
+        }),
+        ..Default::default()
+    }
+    .build()
+    .unwrap()
+}
+
+fn create_request() -> Request {
+    Request {
+        device_token: DEVICE_TOKEN.parse().unwrap(),
+        topic: Some(TOPIC.into()),
+        alert: Some("Hello World!".into()),
+        ..Default::default()
+    }
+}
+
+fn rejection(status: u16, reason: &str) -> ResponseTemplate {
+    ResponseTemplate::new(status).set_body_json(json!({ "reason": reason }))
+}
+
+/// Returns the `apns-id` headers of all requests the mock server received.
+async fn received_apns_ids(mock_server: &MockServer) -> Vec<String> {
+    mock_server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| {
+            let (_, values) = request
+                .headers
+                .iter()
+                .find(|(name, _)| name.as_str() == "apns-id")
+                .expect("missing apns-id header");
+
+            values.last().as_str().to_string()
+        })
+        .collect()
+}
+
+#[test]
+async fn service_unavailable_is_retried_with_the_same_apns_id() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path(format!("/3/device/{DEVICE_TOKEN}")))
+        .respond_with(rejection(503, "ServiceUnavailable"))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path(format!("/3/device/{DEVICE_TOKEN}")))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_retrying_client(&mock_server.uri());
+    let response = client.send(create_request()).await;
+
+    let apns_ids = received_apns_ids(&mock_server).await;
+
+    assert!(response.is_ok());
+    assert_eq!(2, apns_ids.len());
+    assert_eq!(apns_ids[0], apns_ids[1]);
+}
+
+#[test]
+async fn service_unavailable_without_a_body_is_retried() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path(format!("/3/device/{DEVICE_TOKEN}")))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path(format!("/3/device/{DEVICE_TOKEN}")))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_retrying_client(&mock_server.uri());
+
+    assert!(client.send(create_request()).await.is_ok());
+}
+
+#[test]
+async fn too_many_requests_gives_up_after_max_attempts() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(rejection(429, "TooManyRequests"))
+        .expect(3)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_retrying_client(&mock_server.uri());
+    let error = client.send(create_request()).await.unwrap_err();
+
+    assert_eq!(Some(Reason::TooManyRequests), error.reason());
+}
+
+#[test]
+async fn permanent_rejections_are_not_retried() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .respond_with(rejection(400, "BadDeviceToken"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = create_retrying_client(&mock_server.uri());
+    let error = client.send(create_request()).await.unwrap_err();
+
+    assert_eq!(Some(Reason::BadDeviceToken), error.reason());
+}
+
+#[test]
+async fn connection_errors_are_returned_after_retrying() {
+    // Nothing listens on a port that was just released.
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+
+    let client = create_retrying_client(&format!("http://127.0.0.1:{port}"));
+    let error = client.send(create_request()).await.unwrap_err();
+
+    assert!(matches!(error, Error::ConnectionError(_)));
+}
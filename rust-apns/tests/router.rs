@@ -0,0 +1,125 @@
+use rust_apns::*;
+use serde_json::json;
+use tokio::test;
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const DEVICE_TOKEN: &str = "a863a50a904a4bb79380aae1e6c80b4dad25fcf8552848599d979b020aece5ae";
+
+fn create_client(mock_server_uri: &str) -> Client {
+    ClientBuilder {
+        endpoint: Endpoint::Custom(format!("{mock_server_uri}/3/device/").parse().unwrap()),
+        ..Default::default()
+    }
+    .build()
+    .unwrap()
+}
+
+fn create_request(device_token: DeviceToken) -> Request<()> {
+    Request {
+        device_token,
+        alert: Some("Hello World!".into()),
+        ..Default::default()
+    }
+}
+
+async fn mount_bad_device_token(mock_server: &MockServer, expected: u64) {
+    Mock::given(method("POST"))
+        .and(path(format!("/3/device/{DEVICE_TOKEN}")))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({ "reason": "BadDeviceToken" })))
+        .expect(expected)
+        .mount(mock_server)
+        .await;
+}
+
+async fn mount_accepted(mock_server: &MockServer, expected: u64) {
+    Mock::given(method("POST"))
+        .and(path(format!("/3/device/{DEVICE_TOKEN}")))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(expected)
+        .mount(mock_server)
+        .await;
+}
+
+#[test]
+async fn routes_by_environment_hint() {
+    let production = MockServer::start().await;
+    let development = MockServer::start().await;
+
+    mount_accepted(&production, 1).await;
+    mount_accepted(&development, 1).await;
+
+    let router = RouterClient::new(create_client(&production.uri()), create_client(&development.uri()));
+    let device_token: DeviceToken = DEVICE_TOKEN.parse().unwrap();
+
+    let routed = router.send(create_request(device_token.clone())).await.unwrap();
+    assert_eq!(Environment::Production, routed.environment);
+    assert!(!routed.switched_environment);
+
+    let routed = router
+        .send(create_request(device_token.with_environment(Endpoint::Development)))
+        .await
+        .unwrap();
+
+    assert_eq!(Environment::Development, routed.environment);
+    assert!(!routed.switched_environment);
+    assert_eq!(200, routed.response.code);
+}
+
+#[test]
+async fn retries_bad_device_token_in_the_other_environment() {
+    let production = MockServer::start().await;
+    let development = MockServer::start().await;
+
+    mount_bad_device_token(&production, 1).await;
+    mount_accepted(&development, 1).await;
+
+    let router = RouterClient::new(create_client(&production.uri()), create_client(&development.uri()))
+        .with_retry_other_environment(true);
+
+    let routed = router
+        .send(create_request(DEVICE_TOKEN.parse().unwrap()))
+        .await
+        .unwrap();
+
+    assert_eq!(Environment::Development, routed.environment);
+    assert!(routed.switched_environment);
+}
+
+#[test]
+async fn bad_device_token_without_retry() {
+    let production = MockServer::start().await;
+    let development = MockServer::start().await;
+
+    mount_bad_device_token(&production, 1).await;
+    mount_accepted(&development, 0).await;
+
+    let router = RouterClient::new(create_client(&production.uri()), create_client(&development.uri()));
+    let error = router
+        .send(create_request(DEVICE_TOKEN.parse().unwrap()))
+        .await
+        .unwrap_err();
+
+    assert_eq!(Some(Reason::BadDeviceToken), error.reason());
+}
+
+#[test]
+async fn bad_device_token_in_both_environments() {
+    let production = MockServer::start().await;
+    let development = MockServer::start().await;
+
+    mount_bad_device_token(&production, 1).await;
+    mount_bad_device_token(&development, 1).await;
+
+    let router = RouterClient::new(create_client(&production.uri()), create_client(&development.uri()))
+        .with_retry_other_environment(true);
+
+    let error = router
+        .send(create_request(DEVICE_TOKEN.parse().unwrap()))
+        .await
+        .unwrap_err();
+
+    assert_eq!(Some(Reason::BadDeviceToken), error.reason());
+}
@@ -0,0 +1,148 @@
+use futures_util::{stream, StreamExt};
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Server,
+};
+use rust_apns::*;
+use serde_json::json;
+use std::{collections::HashMap, convert::Infallible, net::SocketAddr, time::Duration};
+use tokio::test;
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const DEVICE_TOKEN: &str = "a863a50a904a4bb79380aae1e6c80b4dad25fcf8552848599d979b020aece5ae";
+const TOPIC: &str = "com.example.myapp";
+const UNREGISTERED_TOKEN: &str = "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+
+fn create_apns_client(mock_server_uri: &str) -> Client {
+    ClientBuilder {
+        endpoint: Endpoint::Custom(format!("{mock_server_uri}/3/device/").parse().unwrap()),
+        max_concurrent_streams: 8,
+        ..Default::default()
+    }
+    .build()
+    .unwrap()
+}
+
+fn create_request(device_token: &str) -> Request {
+    Request {
+        device_token: device_token.parse().unwrap(),
+        topic: Some(TOPIC.into()),
+        alert: Some("Hello World!".into()),
+        ..Default::default()
+    }
+}
+
+async fn mount_mocks(mock_server: &MockServer) {
+    Mock::given(method("POST"))
+        .and(path(format!("/3/device/{UNREGISTERED_TOKEN}")))
+        .respond_with(ResponseTemplate::new(410).set_body_json(json!({ "reason": "Unregistered" })))
+        .mount(mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(mock_server)
+        .await;
+}
+
+#[test]
+async fn send_all_yields_a_delivery_per_token() {
+    let mock_server = MockServer::start().await;
+    mount_mocks(&mock_server).await;
+
+    let client = create_apns_client(&mock_server.uri());
+
+    let tokens: Vec<String> = (0..50).map(|i| format!("{i:064x}")).collect();
+    let requests = tokens
+        .iter()
+        .map(|token| create_request(token))
+        .chain(Some(create_request(UNREGISTERED_TOKEN)));
+
+    let deliveries: HashMap<String, Delivery> = client
+        .send_all(requests)
+        .map(|delivery| (delivery.device_token.to_string(), delivery))
+        .collect()
+        .await;
+
+    assert_eq!(51, deliveries.len());
+    assert_eq!(51, mock_server.received_requests().await.unwrap().len());
+
+    for token in &tokens {
+        assert!(deliveries[token].result.is_ok());
+    }
+
+    let unregistered = &deliveries[UNREGISTERED_TOKEN];
+    let error = unregistered.result.as_ref().unwrap_err();
+
+    assert_eq!(Some(Reason::Unregistered), error.reason());
+}
+
+#[test]
+async fn send_stream_keeps_the_apns_id() {
+    let mock_server = MockServer::start().await;
+    mount_mocks(&mock_server).await;
+
+    let client = create_apns_client(&mock_server.uri());
+
+    let apns_id = "4d947500-498e-4524-8aa8-7220c4e65d75".parse().unwrap();
+    let request = Request {
+        id: Some(apns_id),
+        ..create_request(DEVICE_TOKEN)
+    };
+
+    let deliveries: Vec<Delivery> = client.send_stream(stream::iter(vec![request])).collect().await;
+
+    assert_eq!(1, deliveries.len());
+    assert_eq!(apns_id, deliveries[0].apns_id);
+    assert_eq!(deliveries[0].device_token, DEVICE_TOKEN);
+}
+
+/// Starts an HTTP/2 server allowing `max_concurrent_streams` streams per
+/// connection, accepting every notification after `delay`.
+fn start_limited_server(max_concurrent_streams: u32, delay: Duration) -> SocketAddr {
+    let make_service = make_service_fn(move |_| async move {
+        Ok::<_, Infallible>(service_fn(move |_| async move {
+            tokio::time::sleep(delay).await;
+            Ok::<_, Infallible>(hyper::Response::new(Body::empty()))
+        }))
+    });
+
+    let server = Server::bind(&([127, 0, 0, 1], 0).into())
+        .http2_only(true)
+        .http2_max_concurrent_streams(max_concurrent_streams)
+        .serve(make_service);
+
+    let addr = server.local_addr();
+    tokio::spawn(server);
+
+    addr
+}
+
+#[test]
+async fn send_all_waits_for_the_streams_the_server_allows() {
+    let addr = start_limited_server(2, Duration::from_millis(100));
+
+    let client = ClientBuilder {
+        endpoint: Endpoint::Custom(format!("http://{addr}/3/device/").parse().unwrap()),
+        request_timeout: Some(Duration::from_millis(250)),
+        max_concurrent_streams: 100,
+        ..Default::default()
+    }
+    .build()
+    .unwrap();
+
+    let tokens: Vec<String> = (0..10).map(|i| format!("{i:064x}")).collect();
+    let deliveries: Vec<Delivery> = client
+        .send_all(tokens.iter().map(|token| create_request(token)))
+        .collect()
+        .await;
+
+    assert_eq!(10, deliveries.len());
+
+    for delivery in deliveries {
+        assert!(delivery.result.is_ok(), "{:?}", delivery.result);
+    }
+}